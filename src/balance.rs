@@ -0,0 +1,315 @@
+use chrono::{Datelike, Duration, NaiveDate, Weekday};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use crate::entry::{hh_mm, Entry};
+use crate::period::{self, Calendar};
+
+/// The `[balance]` section of the configuration for flextime.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default)]
+pub struct BalanceConfig {
+    /// Count over- and under-time from this date.
+    #[serde(deserialize_with = "crate::config::optional_date")]
+    pub anchor: Option<NaiveDate>,
+    /// Target of each workday, when the weekly hours are not set.
+    #[serde(deserialize_with = "crate::config::optional_hours")]
+    pub daily_hours: Option<f64>,
+    /// Target of a full week shared among the workdays, when the daily hours are not set.
+    #[serde(deserialize_with = "crate::config::optional_hours")]
+    pub weekly_hours: Option<f64>,
+    /// Days with a target, ie ["mon", "tue", "wed", "thu"].
+    pub workdays: Vec<Weekday>,
+    /// Dates or an `.ics` calendar, as for `--exclude-holidays`.
+    pub holidays: Option<PathBuf>,
+    /// Vacation, sick, and other days off.
+    #[serde(deserialize_with = "crate::config::dates")]
+    pub leave: Vec<NaiveDate>,
+}
+
+impl Default for BalanceConfig {
+    fn default() -> Self {
+        Self {
+            anchor: None,
+            daily_hours: None,
+            weekly_hours: None,
+            workdays: vec![
+                Weekday::Mon,
+                Weekday::Tue,
+                Weekday::Wed,
+                Weekday::Thu,
+                Weekday::Fri,
+            ],
+            holidays: None,
+            leave: Vec::new(),
+        }
+    }
+}
+
+impl BalanceConfig {
+    /// Targets with the holidays and leave days off.
+    ///
+    /// Only one of the daily and weekly hours may be set so that days and
+    /// weeks add up to the same balance.
+    ///
+    /// # Arguments
+    /// * `holidays` - Loaded from the `holidays` file, if any.
+    pub fn targets(&self, holidays: &[NaiveDate]) -> Result<Targets, String> {
+        let workdays = self.workdays.len() as f64;
+        let (daily, weekly) = match (self.daily_hours, self.weekly_hours) {
+            (Some(_), Some(_)) => {
+                return Err("balance needs daily_hours or weekly_hours, not both".to_string())
+            }
+            (Some(daily), None) => (daily, daily * workdays),
+            (None, Some(weekly)) if workdays > 0.0 => (weekly / workdays, weekly),
+            _ => return Err("balance needs daily_hours or weekly_hours and workdays".to_string()),
+        };
+        let mut days_off: Vec<NaiveDate> = holidays.iter().chain(&self.leave).copied().collect();
+        days_off.sort();
+        days_off.dedup();
+        Ok(Targets {
+            daily: hours(daily),
+            weekly: hours(weekly),
+            workdays: self.workdays.clone(),
+            days_off,
+        })
+    }
+}
+
+fn hours(hours: f64) -> Duration {
+    Duration::seconds((hours * 3600.0).round() as i64)
+}
+
+/// Expected time per day and week.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Targets {
+    pub daily: Duration,
+    pub weekly: Duration,
+    pub workdays: Vec<Weekday>,
+    /// Sorted holidays and leave days.
+    pub days_off: Vec<NaiveDate>,
+}
+
+impl Targets {
+    /// Whether the date has a target.
+    pub fn is_workday(&self, date: NaiveDate) -> bool {
+        self.workdays.contains(&date.weekday()) && self.days_off.binary_search(&date).is_err()
+    }
+
+    /// The daily target, or zero on weekends, holidays, and leave.
+    pub fn day(&self, date: NaiveDate) -> Duration {
+        match self.is_workday(date) {
+            true => self.daily,
+            false => Duration::zero(),
+        }
+    }
+
+    /// The weekly target shared among the workdays of the week that are counted.
+    ///
+    /// # Arguments
+    /// * `days` - The days of one week counted, ie all but those before the anchor.
+    pub fn week(&self, days: impl Iterator<Item = NaiveDate>) -> Duration {
+        let counted = days.filter(|day| self.is_workday(*day)).count() as i32;
+        match self.workdays.len() as i32 {
+            0 => Duration::zero(),
+            workdays => self.weekly * counted / workdays,
+        }
+    }
+}
+
+/// Time worked against the target for a day or week.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Balance {
+    pub label: String,
+    pub worked: Duration,
+    pub target: Duration,
+}
+
+impl Balance {
+    /// Overtime when positive and undertime when negative.
+    pub fn over(&self) -> Duration {
+        self.worked - self.target
+    }
+}
+
+/// Time worked on each workday of the entries.
+pub fn worked(entries: &[Entry], calendar: &Calendar) -> BTreeMap<NaiveDate, Duration> {
+    let mut days = BTreeMap::new();
+    for entry in entries {
+        *days
+            .entry(calendar.workday(entry.begin))
+            .or_insert_with(Duration::zero) += entry.duration();
+    }
+    days
+}
+
+/// Balance of every day from first through last.
+pub fn days(
+    worked: &BTreeMap<NaiveDate, Duration>,
+    first: NaiveDate,
+    last: NaiveDate,
+    targets: &Targets,
+) -> Vec<Balance> {
+    first
+        .iter_days()
+        .take_while(|day| *day <= last)
+        .map(|day| Balance {
+            label: day.to_string(),
+            worked: worked.get(&day).copied().unwrap_or_else(Duration::zero),
+            target: targets.day(day),
+        })
+        .collect()
+}
+
+/// Balance of every week from first through last.
+///
+/// Weeks cut by first or last only count their days within.
+pub fn weeks(
+    worked: &BTreeMap<NaiveDate, Duration>,
+    first: NaiveDate,
+    last: NaiveDate,
+    targets: &Targets,
+    week_start: Weekday,
+) -> Vec<Balance> {
+    let mut weeks = Vec::new();
+    let mut start = first;
+    while start <= last {
+        let (week_first, week_last) = period::week(start, week_start);
+        let end = week_last.min(last);
+        let days = || start.iter_days().take_while(move |day| *day <= end);
+        weeks.push(Balance {
+            label: format!("{}..{}", week_first, week_last),
+            worked: days()
+                .filter_map(|day| worked.get(&day))
+                .fold(Duration::zero(), |sum, duration| sum + *duration),
+            target: targets.week(days()),
+        });
+        start = end + Duration::days(1);
+    }
+    weeks
+}
+
+/// A duration with its sign, ie "+01:30" or "-00:15".
+pub fn signed(duration: &Duration) -> String {
+    match *duration < Duration::zero() {
+        true => format!("-{}", hh_mm(&-*duration)),
+        false => format!("+{}", hh_mm(duration)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::date_filter::ymd;
+    use rstest::rstest;
+
+    fn targets(daily: Option<f64>, weekly: Option<f64>) -> Targets {
+        let config = BalanceConfig {
+            daily_hours: daily,
+            weekly_hours: weekly,
+            leave: vec![ymd(2024, 4, 3)],
+            ..BalanceConfig::default()
+        };
+        config.targets(&[ymd(2024, 4, 1)]).unwrap()
+    }
+
+    #[rstest]
+    #[case(Some(8.0), None, 480, 2400)]
+    #[case(None, Some(40.0), 480, 2400)]
+    fn test_targets(
+        #[case] daily: Option<f64>,
+        #[case] weekly: Option<f64>,
+        #[case] day: i64,
+        #[case] week: i64,
+    ) {
+        let targets = targets(daily, weekly);
+        assert_eq!(targets.daily, Duration::minutes(day));
+        assert_eq!(targets.weekly, Duration::minutes(week));
+    }
+
+    #[rstest]
+    #[case(None, None)]
+    #[case(Some(9.0), Some(40.0))]
+    fn test_targets_bad(#[case] daily: Option<f64>, #[case] weekly: Option<f64>) {
+        let config = BalanceConfig {
+            daily_hours: daily,
+            weekly_hours: weekly,
+            ..BalanceConfig::default()
+        };
+        assert!(config.targets(&[]).is_err());
+    }
+
+    #[test]
+    fn test_days_off() {
+        let targets = targets(Some(8.0), None);
+        assert_eq!(targets.day(ymd(2024, 4, 1)), Duration::zero());
+        assert_eq!(targets.day(ymd(2024, 4, 2)), Duration::hours(8));
+        assert_eq!(targets.day(ymd(2024, 4, 3)), Duration::zero());
+        assert_eq!(targets.day(ymd(2024, 4, 6)), Duration::zero());
+        let week = ymd(2024, 4, 1).iter_days().take(7);
+        assert_eq!(targets.week(week), Duration::hours(24));
+    }
+
+    #[test]
+    fn test_weeks() {
+        let targets = targets(None, Some(40.0));
+        let worked: BTreeMap<NaiveDate, Duration> = [
+            (ymd(2024, 4, 4), Duration::hours(9)),
+            (ymd(2024, 4, 5), Duration::hours(8)),
+            (ymd(2024, 4, 8), Duration::hours(7)),
+        ]
+        .into_iter()
+        .collect();
+        let weeks = weeks(
+            &worked,
+            ymd(2024, 4, 4),
+            ymd(2024, 4, 8),
+            &targets,
+            Weekday::Mon,
+        );
+        let actual: Vec<(&str, String)> = weeks
+            .iter()
+            .map(|week| (week.label.as_str(), signed(&week.over())))
+            .collect();
+        assert_eq!(
+            actual,
+            vec![
+                ("2024-04-01..2024-04-07", "+01:00".to_string()),
+                ("2024-04-08..2024-04-14", "-01:00".to_string())
+            ]
+        );
+        let days = days(&worked, ymd(2024, 4, 4), ymd(2024, 4, 8), &targets);
+        let total = days
+            .iter()
+            .fold(Duration::zero(), |sum, day| sum + day.over());
+        assert_eq!(total, Duration::zero());
+    }
+
+    #[test]
+    fn test_config() {
+        let config = crate::Config::from_toml(concat!(
+            "[balance]\n",
+            "anchor = 2024-01-01\n",
+            "weekly_hours = 32.0\n",
+            "workdays = [\"mon\", \"tue\", \"wed\", \"thu\"]\n",
+            "leave = [2024-07-01, \"2024-07-02\"]\n",
+        ))
+        .unwrap();
+        let balance = config.balance;
+        assert_eq!(balance.anchor, Some(ymd(2024, 1, 1)));
+        assert_eq!(balance.workdays.len(), 4);
+        assert_eq!(balance.leave, vec![ymd(2024, 7, 1), ymd(2024, 7, 2)]);
+        let targets = balance.targets(&[]).unwrap();
+        assert_eq!(targets.daily, Duration::hours(8));
+        assert_eq!(targets.day(ymd(2024, 4, 5)), Duration::zero());
+    }
+
+    #[rstest]
+    #[case("weekly_hours = 1e300")]
+    #[case("daily_hours = -8.0")]
+    #[case("daily_hours = nan")]
+    fn test_config_bad_hours(#[case] hours: &str) {
+        assert!(crate::Config::from_toml(&format!("[balance]\n{}", hours)).is_err());
+    }
+}
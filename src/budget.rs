@@ -0,0 +1,236 @@
+use chrono::{Datelike, Duration, NaiveDate};
+use serde::Deserialize;
+
+use crate::entry::Entry;
+use crate::period::{self, Calendar};
+
+/// Share of a budget, in percent, that is worth a warning.
+pub const THRESHOLDS: [u32; 2] = [80, 100];
+
+/// How often a budget starts over.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub enum BudgetPeriod {
+    Week,
+    Month,
+    Quarter,
+    Year,
+    /// The pay_period from the `[calendar]` config
+    PayPeriod,
+    /// Quarters of the fiscal year from the `[calendar]` config
+    FiscalQuarter,
+    FiscalYear,
+}
+
+/// Hours allowed for a tag each period, from a `[[budget]]` config section.
+///
+/// # Example
+/// ```toml
+/// [[budget]]
+/// tag = "+acme"
+/// hours = 120.0
+/// period = "quarter"
+/// ```
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+pub struct Budget {
+    pub tag: String,
+    #[serde(deserialize_with = "crate::config::hours")]
+    pub hours: f64,
+    pub period: BudgetPeriod,
+}
+
+impl Budget {
+    /// The hours as a duration.
+    pub fn time(&self) -> Duration {
+        Duration::seconds((self.hours * 3600.0).round() as i64)
+    }
+
+    /// First and last day of the budget period containing the date.
+    pub fn span(
+        &self,
+        date: NaiveDate,
+        calendar: &Calendar,
+    ) -> Result<(NaiveDate, NaiveDate), String> {
+        let span = match self.period {
+            BudgetPeriod::Week => Some(period::week(date, calendar.week_start)),
+            BudgetPeriod::Month => period::month(date.year(), date.month()),
+            BudgetPeriod::Quarter => period::quarter(date.year(), period::quarter_of(date)),
+            BudgetPeriod::Year => period::year(date.year()),
+            BudgetPeriod::PayPeriod => Some(
+                calendar
+                    .pay_period(date)
+                    .ok_or("a pay-period budget needs a pay_period in the [calendar] config")?,
+            ),
+            BudgetPeriod::FiscalQuarter => {
+                let (year, quarter) = calendar.fiscal_quarter_of(date);
+                calendar.fiscal_quarter(year, quarter)
+            }
+            BudgetPeriod::FiscalYear => calendar.fiscal_year(calendar.fiscal_year_of(date)),
+        };
+        span.ok_or_else(|| format!("no {:?} budget period for {}", self.period, date))
+    }
+
+    /// Time used by entries with the tag that begin within the span.
+    ///
+    /// # Arguments
+    /// * `entries` - Any entries; others are ignored, ie from pick over the span.
+    pub fn usage(
+        &self,
+        entries: &[Entry],
+        span: (NaiveDate, NaiveDate),
+        calendar: &Calendar,
+    ) -> Usage {
+        let (first, last) = span;
+        let used = entries
+            .iter()
+            .filter(|entry| entry.tags().contains(&self.tag.as_str()))
+            .filter(|entry| (first..=last).contains(&calendar.workday(entry.begin)))
+            .fold(Duration::zero(), |sum, entry| sum + entry.duration());
+        Usage {
+            first,
+            last,
+            used,
+            budget: self.time(),
+        }
+    }
+}
+
+/// Time used of a budget in one period.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Usage {
+    pub first: NaiveDate,
+    pub last: NaiveDate,
+    pub used: Duration,
+    pub budget: Duration,
+}
+
+impl Usage {
+    /// Time left, which is negative once over budget.
+    pub fn remaining(&self) -> Duration {
+        self.budget - self.used
+    }
+
+    pub fn percent(&self) -> f64 {
+        match self.budget.num_seconds() {
+            0 => 0.0,
+            seconds => self.used.num_seconds() as f64 * 100.0 / seconds as f64,
+        }
+    }
+
+    /// The highest threshold reached by the last `added` time of the usage.
+    ///
+    /// # Returns
+    /// None when the threshold was already reached before it was added.
+    pub fn crossed(&self, added: Duration) -> Option<u32> {
+        let before = Usage {
+            used: self.used - added,
+            ..self.clone()
+        };
+        THRESHOLDS
+            .iter()
+            .rev()
+            .find(|threshold| {
+                let threshold = **threshold as f64;
+                before.percent() < threshold && self.percent() >= threshold
+            })
+            .copied()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::date_filter::ymd;
+    use crate::Config;
+    use chrono::DateTime;
+    use rstest::rstest;
+
+    fn budget(period: BudgetPeriod) -> Budget {
+        Budget {
+            tag: "+acme".to_string(),
+            hours: 10.0,
+            period,
+        }
+    }
+
+    fn entry(begin: &str, minutes: i64, text: &str) -> Entry {
+        let begin = DateTime::parse_from_rfc3339(begin).unwrap();
+        Entry {
+            begin,
+            cease: begin + Duration::minutes(minutes),
+            text: text.to_string(),
+        }
+    }
+
+    #[rstest]
+    #[case(BudgetPeriod::Week, ymd(2024, 5, 13), ymd(2024, 5, 19))]
+    #[case(BudgetPeriod::Month, ymd(2024, 5, 1), ymd(2024, 5, 31))]
+    #[case(BudgetPeriod::Quarter, ymd(2024, 4, 1), ymd(2024, 6, 30))]
+    #[case(BudgetPeriod::Year, ymd(2024, 1, 1), ymd(2024, 12, 31))]
+    #[case(BudgetPeriod::FiscalQuarter, ymd(2024, 4, 1), ymd(2024, 6, 30))]
+    #[case(BudgetPeriod::FiscalYear, ymd(2023, 10, 1), ymd(2024, 9, 30))]
+    fn test_span(#[case] period: BudgetPeriod, #[case] first: NaiveDate, #[case] last: NaiveDate) {
+        let calendar = Calendar {
+            fiscal_year_start: 10,
+            ..Calendar::default()
+        };
+        assert_eq!(
+            budget(period).span(ymd(2024, 5, 15), &calendar),
+            Ok((first, last))
+        );
+    }
+
+    #[test]
+    fn test_span_pay_period() {
+        let budget = budget(BudgetPeriod::PayPeriod);
+        assert!(budget.span(ymd(2024, 5, 15), &Calendar::default()).is_err());
+    }
+
+    #[test]
+    fn test_usage() {
+        let entries = vec![
+            entry("2024-05-15T09:00:00Z", 300, "+acme build"),
+            entry("2024-05-14T09:00:00Z", 180, "+acme @CJ review"),
+            entry("2024-05-13T09:00:00Z", 60, "+other"),
+            entry("2024-04-30T09:00:00Z", 600, "+acme last month"),
+        ];
+        let calendar = Calendar::default();
+        let budget = budget(BudgetPeriod::Month);
+        let span = budget.span(ymd(2024, 5, 15), &calendar).unwrap();
+        let usage = budget.usage(&entries, span, &calendar);
+        assert_eq!(usage.used, Duration::hours(8));
+        assert_eq!(usage.remaining(), Duration::hours(2));
+        assert_eq!(usage.percent(), 80.0);
+        assert_eq!(usage.crossed(Duration::hours(5)), Some(80));
+        assert_eq!(usage.crossed(Duration::zero()), None);
+    }
+
+    #[rstest]
+    #[case(9, 0, None)]
+    #[case(9, 2, Some(80))]
+    #[case(11, 3, Some(100))]
+    #[case(12, 1, None)]
+    fn test_crossed(#[case] used: i64, #[case] added: i64, #[case] expected: Option<u32>) {
+        let usage = Usage {
+            first: ymd(2024, 5, 1),
+            last: ymd(2024, 5, 31),
+            used: Duration::hours(used),
+            budget: Duration::hours(10),
+        };
+        assert_eq!(usage.crossed(Duration::hours(added)), expected);
+    }
+
+    #[test]
+    fn test_config() {
+        let config = Config::from_toml(concat!(
+            "[[budget]]\ntag = \"+acme\"\nhours = 120.0\nperiod = \"quarter\"\n",
+            "[[budget]]\ntag = \"+beta\"\nhours = 10\nperiod = \"pay-period\"\n",
+        ))
+        .unwrap();
+        assert_eq!(config.budgets.len(), 2);
+        assert_eq!(config.budgets[0].time(), Duration::hours(120));
+        assert_eq!(config.budgets[1].period, BudgetPeriod::PayPeriod);
+        let huge = "[[budget]]\ntag = \"+acme\"\nhours = 1e300\nperiod = \"year\"\n";
+        assert!(Config::from_toml(huge).is_err());
+    }
+}
@@ -0,0 +1,148 @@
+use chrono::Duration;
+
+use crate::entry::hh_mm;
+use crate::report::Row;
+
+/// Eighths of a block, from none to full.
+const EIGHTHS: [char; 9] = [' ', '▏', '▎', '▍', '▌', '▋', '▊', '▉', '█'];
+/// Sparkline levels from nothing to the most.
+const SPARKS: [char; 9] = [' ', '▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+const ASCII_SPARKS: [char; 9] = [' ', '.', ':', '-', '=', '+', '*', '#', '@'];
+
+/// Terminal width from $COLUMNS, or 80.
+pub fn terminal_width() -> usize {
+    std::env::var("COLUMNS")
+        .ok()
+        .and_then(|columns| columns.trim().parse().ok())
+        .filter(|columns| *columns > 0)
+        .unwrap_or(80)
+}
+
+/// Rows as horizontal bars scaled so the longest fills the width.
+///
+/// # Arguments
+/// * `width` - Columns for the whole line, including the label and time.
+/// * `unicode` - Block characters with eighths; otherwise '#'.
+///
+/// # Returns
+/// One line per row, ie "2024-04-01  07:30 ███████▌".
+pub fn bars(rows: &[Row], width: usize, unicode: bool) -> Vec<String> {
+    let label_width = rows.iter().map(|row| row.label.chars().count()).max();
+    let time_width = rows.iter().map(|row| hh_mm(&row.duration).len()).max();
+    let longest = rows
+        .iter()
+        .map(|row| row.duration.num_seconds())
+        .max()
+        .unwrap_or_default();
+    let (label_width, time_width) = (label_width.unwrap_or(0), time_width.unwrap_or(0));
+    let bar_width = width.saturating_sub(label_width + time_width + 3).max(1);
+
+    rows.iter()
+        .map(|row| {
+            let bar = bar(row.duration, longest, bar_width, unicode);
+            let line = format!(
+                "{:<label_width$}  {:>time_width$} {}",
+                row.label,
+                hh_mm(&row.duration),
+                bar
+            );
+            line.trim_end().to_string()
+        })
+        .collect()
+}
+
+fn bar(duration: Duration, longest: i64, width: usize, unicode: bool) -> String {
+    if longest <= 0 {
+        return String::new();
+    }
+    let eighths =
+        (duration.num_seconds().max(0) as u128 * width as u128 * 8 / longest as u128) as usize;
+    match unicode {
+        true => {
+            let full = EIGHTHS[8].to_string().repeat(eighths / 8);
+            match eighths % 8 {
+                0 => full,
+                part => format!("{}{}", full, EIGHTHS[part]),
+            }
+        }
+        false => "#".repeat((eighths + 4) / 8),
+    }
+}
+
+/// One character per value, taller for more time.
+///
+/// Zero is a space and anything more is at least the lowest mark, so a
+/// short day still shows.
+pub fn sparkline(values: &[Duration], unicode: bool) -> String {
+    let levels = match unicode {
+        true => SPARKS,
+        false => ASCII_SPARKS,
+    };
+    let most = values
+        .iter()
+        .map(|value| value.num_seconds())
+        .max()
+        .unwrap_or_default();
+    values
+        .iter()
+        .map(|value| match value.num_seconds() {
+            seconds if seconds <= 0 || most <= 0 => levels[0],
+            seconds => levels[1 + ((seconds - 1) * 8 / most) as usize],
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    fn row(label: &str, minutes: i64) -> Row {
+        Row {
+            label: label.to_string(),
+            duration: Duration::minutes(minutes),
+        }
+    }
+
+    #[test]
+    fn test_bars() {
+        let rows = vec![row("+acme", 480), row("@help", 60), row("(untagged)", 0)];
+        assert_eq!(
+            bars(&rows, 34, true),
+            vec![
+                "+acme       08:00 ████████████████",
+                "@help       01:00 ██",
+                "(untagged)  00:00",
+            ]
+        );
+        assert_eq!(
+            bars(&rows, 30, false),
+            vec![
+                "+acme       08:00 ############",
+                "@help       01:00 ##",
+                "(untagged)  00:00",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_bars_eighths() {
+        let rows = vec![row("a", 80), row("b", 15)];
+        let lines = bars(&rows, 17, true);
+        assert_eq!(lines[1], "b  00:15 █▌");
+    }
+
+    #[rstest]
+    #[case(true, "▁▄█ ")]
+    #[case(false, ".=@ ")]
+    fn test_sparkline(#[case] unicode: bool, #[case] expected: &str) {
+        let values: Vec<Duration> = [1, 240, 480, 0].map(Duration::minutes).to_vec();
+        assert_eq!(sparkline(&values, unicode), expected);
+    }
+
+    #[test]
+    fn test_sparkline_empty() {
+        assert_eq!(sparkline(&[], true), "");
+        assert_eq!(sparkline(&[Duration::zero()], true), " ");
+    }
+}
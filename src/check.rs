@@ -0,0 +1,157 @@
+use chrono::{DateTime, FixedOffset};
+use chrono_tz::Tz;
+use std::fmt;
+use std::io::BufRead;
+
+use crate::entry::Entry;
+
+/// How bad a problem in the TSV is.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Severity {
+    /// Other commands will fail or give wrong durations.
+    Error,
+    /// Probably a mistake, ie a clock changed mid-day.
+    Warning,
+}
+
+/// A problem found on one line of the TSV.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Issue {
+    pub line: usize,
+    pub severity: Severity,
+    pub message: String,
+}
+
+impl fmt::Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let severity = match self.severity {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        };
+        write!(f, "line {}: {}: {}", self.line, severity, self.message)
+    }
+}
+
+/// Validate every line of a TSV.
+///
+/// Errors are lines that do not parse and timestamps earlier than the line
+/// before. A warning is given when the offset changes within a day, which
+/// usually means travel or a daylight saving change.
+///
+/// # Arguments
+/// * `reader` - The TSV, oldest line first.
+/// * `zone` - Decide what day a timestamp is in here rather than as written.
+pub fn check(reader: impl BufRead, zone: Option<Tz>) -> Vec<Issue> {
+    let mut issues = Vec::new();
+    let mut previous: Option<(usize, DateTime<FixedOffset>)> = None;
+    for (index, line) in reader.lines().enumerate() {
+        let number = index + 1;
+        let mut issue = |severity, message: String| {
+            issues.push(Issue {
+                line: number,
+                severity,
+                message,
+            })
+        };
+        let line = match line {
+            Ok(line) => line,
+            Err(e) => {
+                issue(Severity::Error, e.to_string());
+                continue;
+            }
+        };
+        if line.trim().is_empty() {
+            issue(Severity::Error, "blank line".to_string());
+            continue;
+        }
+        let when = match Entry::from_tsv(line.trim()) {
+            Ok((when, _)) => when,
+            Err(e) => {
+                issue(Severity::Error, e);
+                continue;
+            }
+        };
+        if !line.contains('\t') {
+            issue(Severity::Warning, "no tab before the text".to_string());
+        }
+
+        if let Some((previous_number, before)) = previous {
+            if when < before {
+                issue(
+                    Severity::Error,
+                    format!("earlier than line {}", previous_number),
+                );
+            }
+            let day = |time: DateTime<FixedOffset>| match zone {
+                Some(zone) => time.with_timezone(&zone).date_naive(),
+                None => time.date_naive(),
+            };
+            if before.offset() != when.offset() && day(before) == day(when) {
+                issue(
+                    Severity::Warning,
+                    format!(
+                        "offset changed from {} to {} on {}",
+                        before.offset(),
+                        when.offset(),
+                        day(when)
+                    ),
+                );
+            }
+        }
+        previous = Some((number, when));
+    }
+    issues
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io;
+
+    #[test]
+    fn test_check_clean() {
+        let source = concat!(
+            "2024-04-01T08:00:00-05:00\t*~*~*--------------------\n",
+            "2024-04-01T09:00:00-05:00\tplanning\n",
+            "2024-04-02T09:00:00+02:00\tafter the flight\n",
+        );
+        assert_eq!(check(io::Cursor::new(source), None), vec![]);
+    }
+
+    #[test]
+    fn test_check_issues() {
+        let source = concat!(
+            "2024-04-01T08:00:00-05:00\t*~*~*--------------------\n",
+            "2024-04-01T09:00:00-05:00\tplanning\n",
+            "\n",
+            "yesterday\tforgot the time\n",
+            "2024-04-01T08:30:00-05:00\tout of order\n",
+            "2024-04-01T17:00:00-04:00\tclock changed\n",
+        );
+        let issues: Vec<String> = check(io::Cursor::new(source), None)
+            .iter()
+            .map(|issue| issue.to_string())
+            .collect();
+        assert_eq!(issues.len(), 4);
+        assert_eq!(issues[0], "line 3: error: blank line");
+        assert!(issues[1].starts_with("line 4: error: DateTime parser error"));
+        assert_eq!(issues[2], "line 5: error: earlier than line 2");
+        assert_eq!(
+            issues[3],
+            "line 6: warning: offset changed from -05:00 to -04:00 on 2024-04-01"
+        );
+    }
+
+    #[test]
+    fn test_check_in_zone() {
+        // Different days as written, but the same day in UTC
+        let source = concat!(
+            "2024-04-01T23:00:00-01:00\tlate\n",
+            "2024-04-02T01:00:00+01:00\tearly\n",
+        );
+        assert_eq!(check(io::Cursor::new(source), None), vec![]);
+        let issues = check(io::Cursor::new(source), Some(chrono_tz::UTC));
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].severity, Severity::Warning);
+    }
+}
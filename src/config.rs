@@ -0,0 +1,337 @@
+use chrono::{NaiveDate, NaiveTime};
+use serde::{Deserialize, Deserializer};
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::io::Error;
+use std::path::{Path, PathBuf};
+
+use crate::balance::BalanceConfig;
+use crate::budget::Budget;
+use crate::interruptions::InterruptionConfig;
+use crate::invoice::InvoiceConfig;
+use crate::period::Calendar;
+use crate::tsv::is_existing_file;
+
+/// Settings from the TOML configuration file.
+///
+/// Every section is optional, so a missing file is the same as an empty one.
+///
+/// # Example
+/// ```toml
+/// [calendar]
+/// week_start = "sun"
+/// fiscal_year_start = 10
+/// day_starts_at = "18:00"
+/// pay_period = { kind = "biweekly", anchor = 2024-01-05 }
+///
+/// [balance]
+/// anchor = 2024-01-01
+/// weekly_hours = 40.0
+/// holidays = "holidays.ics"
+/// leave = [2024-07-01, 2024-07-02]
+///
+/// [[budget]]
+/// tag = "+acme"
+/// hours = 120.0
+/// period = "quarter"
+///
+/// [interruptions]
+/// minutes = 5
+/// tags = ["@help"]
+/// breaks = ["lunch", "+break"]
+///
+/// [invoice]
+/// currency = "$"
+/// tax_rate = 0.08
+///
+/// [[invoice.rates]]
+/// tag = "+acme"
+/// hourly = 120.0
+/// ```
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    pub calendar: Calendar,
+    pub balance: BalanceConfig,
+    /// Hours per period for a tag, from each `[[budget]]`.
+    #[serde(rename = "budget")]
+    pub budgets: Vec<Budget>,
+    pub interruptions: InterruptionConfig,
+    pub invoice: InvoiceConfig,
+    /// Why each unusable section of `SECTIONS` was left at its default.
+    #[serde(skip)]
+    errors: BTreeMap<String, String>,
+}
+
+/// Sections only some commands read, so only those commands fail on them.
+const SECTIONS: [&str; 4] = ["balance", "budget", "interruptions", "invoice"];
+
+impl Config {
+    /// Read the configuration from a TOML file.
+    ///
+    /// An unusable section of `SECTIONS` is left at its default with the
+    /// reason kept for `section`; any other problem is an error.
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let content = fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        let mut table: toml::Table =
+            toml::from_str(&content).map_err(|e| format!("{}: {}", path.display(), e))?;
+        let mut errors = BTreeMap::new();
+        for name in SECTIONS {
+            let Some(value) = table.remove(name) else {
+                continue;
+            };
+            let section = toml::Table::from_iter([(name.to_string(), value.clone())]);
+            match toml::Value::Table(section).try_into::<Self>() {
+                Ok(_) => {
+                    table.insert(name.to_string(), value);
+                }
+                Err(e) => {
+                    let reason = format!("{}: [{}] {}", path.display(), name, e.message());
+                    errors.insert(name.to_string(), reason);
+                }
+            }
+        }
+        let mut config: Self = toml::Value::Table(table)
+            .try_into()
+            .map_err(|e: toml::de::Error| format!("{}: {}", path.display(), e.message()))?;
+        config.errors = errors;
+        Ok(config)
+    }
+
+    /// Read the configuration from TOML text.
+    pub fn from_toml(content: &str) -> Result<Self, String> {
+        toml::from_str(content).map_err(|e| e.to_string())
+    }
+
+    /// Whether a section is usable.
+    ///
+    /// # Returns
+    /// The reason the section was left at its default, if it was.
+    pub fn section(&self, name: &str) -> Result<(), String> {
+        match self.errors.get(name) {
+            Some(reason) => Err(reason.clone()),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Get the path to the configuration file.
+///
+/// # Arguments
+/// * `config` - An optional `std::path::PathBuf` given by the user.
+///   The possible sources of this path in order of preference.
+///   1. Given std::path::PathBuf, which must exist.
+///   2. The environment variable $ididCONFIG, which must exist.
+///   3. $XDG_CONFIG_HOME/idid/config.toml or ~/.config/idid/config.toml
+///      when the file exists.
+///
+/// # Returns
+/// The path or None when there is no configuration file.
+pub fn get_config_path(config: &Option<PathBuf>) -> Result<Option<PathBuf>, Error> {
+    if let Some(path) = config {
+        return is_existing_file(path, "--config ").map(Some);
+    }
+    let idid_config = "ididCONFIG";
+    if let Ok(value) = env::var(idid_config) {
+        let prefix = format!("${} ", idid_config);
+        return is_existing_file(&PathBuf::from(value), &prefix).map(Some);
+    }
+    Ok(config_dir()
+        .map(|dir| dir.join("config.toml"))
+        .filter(|path| path.is_file()))
+}
+
+/// A TOML date or time, ie `2024-04-01`, or the same as a string.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum DateValue {
+    Toml(toml::value::Datetime),
+    Text(String),
+}
+
+impl DateValue {
+    fn into_date(self) -> Result<NaiveDate, String> {
+        let text = match self {
+            DateValue::Toml(datetime) => datetime.to_string(),
+            DateValue::Text(text) => text,
+        };
+        NaiveDate::parse_from_str(&text, "%Y-%m-%d")
+            .map_err(|e| format!("invalid date {:?}: {}", text, e))
+    }
+}
+
+/// Deserialize a date for `#[serde(deserialize_with = "...")]`.
+pub(crate) fn date<'de, D: Deserializer<'de>>(deserializer: D) -> Result<NaiveDate, D::Error> {
+    DateValue::deserialize(deserializer)?
+        .into_date()
+        .map_err(serde::de::Error::custom)
+}
+
+/// Deserialize a list of dates for `#[serde(default, deserialize_with = "...")]`.
+pub(crate) fn dates<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Vec<NaiveDate>, D::Error> {
+    Vec::<DateValue>::deserialize(deserializer)?
+        .into_iter()
+        .map(DateValue::into_date)
+        .collect::<Result<_, _>>()
+        .map_err(serde::de::Error::custom)
+}
+
+/// Deserialize an optional date for `#[serde(default, deserialize_with = "...")]`.
+pub(crate) fn optional_date<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<NaiveDate>, D::Error> {
+    date(deserializer).map(Some)
+}
+
+/// Most hours in a config, the hours of a leap year.
+const MOST_HOURS: f64 = 8784.0;
+
+/// Deserialize hours for `#[serde(deserialize_with = "...")]`.
+pub(crate) fn hours<'de, D: Deserializer<'de>>(deserializer: D) -> Result<f64, D::Error> {
+    let hours = f64::deserialize(deserializer)?;
+    match (0.0..=MOST_HOURS).contains(&hours) {
+        true => Ok(hours),
+        false => Err(serde::de::Error::custom(format!(
+            "invalid hours {:?}; use 0 to {}",
+            hours, MOST_HOURS
+        ))),
+    }
+}
+
+/// Deserialize optional hours for `#[serde(default, deserialize_with = "...")]`.
+pub(crate) fn optional_hours<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<f64>, D::Error> {
+    hours(deserializer).map(Some)
+}
+
+/// Deserialize a time of day, ie `18:00:00` or "18:00", for `#[serde(deserialize_with = "...")]`.
+pub(crate) fn time<'de, D: Deserializer<'de>>(deserializer: D) -> Result<NaiveTime, D::Error> {
+    let text = match DateValue::deserialize(deserializer)? {
+        DateValue::Toml(datetime) => datetime.to_string(),
+        DateValue::Text(text) => text,
+    };
+    NaiveTime::parse_from_str(&text, "%H:%M")
+        .or_else(|_| NaiveTime::parse_from_str(&text, "%H:%M:%S"))
+        .map_err(|e| serde::de::Error::custom(format!("invalid time {:?}: {}", text, e)))
+}
+
+/// The idid directory within $XDG_CONFIG_HOME or ~/.config
+pub fn config_dir() -> Option<PathBuf> {
+    env::var("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|_| env::var("HOME").map(|home| PathBuf::from(home).join(".config")))
+        .ok()
+        .map(|dir| dir.join("idid"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_config_empty() {
+        let config = Config::from_toml("").unwrap();
+        assert!(config.invoice.rates.is_empty());
+    }
+
+    #[test]
+    fn test_load_unusable_section() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+        fs::write(
+            &path,
+            concat!(
+                "[calendar]\nweek_start = \"sun\"\n",
+                "[balance]\nanchor = 2024-01-01\nweekly_hours = 1e300\n",
+                "[[budget]]\ntag = \"+acme\"\nhours = 10\nperiod = \"month\"\n",
+                "[[invoice.rates]]\ntag = \"+a\"\nhourly = 1.0\nfrom = 2024-04-01\n",
+            ),
+        )
+        .unwrap();
+        let config = Config::load(&path).unwrap();
+        assert_eq!(config.calendar.week_start, chrono::Weekday::Sun);
+        assert_eq!(config.balance.anchor, None);
+        assert!(config.section("balance").unwrap_err().contains("[balance]"));
+        assert_eq!(config.section("budget"), Ok(()));
+        assert_eq!(config.budgets.len(), 1);
+        assert_eq!(config.invoice.rates.len(), 1);
+
+        fs::write(&path, "[calendar]\nweek_start = \"someday\"\n").unwrap();
+        assert!(Config::load(&path).is_err());
+    }
+
+    #[test]
+    fn test_config_dates() {
+        let config = Config::from_toml(concat!(
+            "[[invoice.rates]]\ntag = \"+a\"\nhourly = 1.0\nfrom = 2024-04-01\n",
+            "[[invoice.rates]]\ntag = \"+b\"\nhourly = 1.0\nfrom = \"2024-04-02\"\n",
+            "[[invoice.rates]]\ntag = \"+c\"\nhourly = 1.0\n",
+        ))
+        .unwrap();
+        let from: Vec<Option<NaiveDate>> = config.invoice.rates.iter().map(|r| r.from).collect();
+        assert_eq!(
+            from,
+            vec![
+                NaiveDate::from_ymd_opt(2024, 4, 1),
+                NaiveDate::from_ymd_opt(2024, 4, 2),
+                None
+            ]
+        );
+        assert!(Config::from_toml(
+            "[[invoice.rates]]\ntag = \"+a\"\nhourly = 1.0\nfrom = 2024-04-01T08:00:00"
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_config_calendar() {
+        let config = Config::from_toml("[calendar]\nweek_start = \"sun\"").unwrap();
+        assert_eq!(config.calendar.week_start, chrono::Weekday::Sun);
+        assert_eq!(Config::default().calendar.week_start, chrono::Weekday::Mon);
+        let config = Config::from_toml("[calendar]\ntimezone = \"America/Chicago\"").unwrap();
+        assert_eq!(config.calendar.timezone, Some(chrono_tz::America::Chicago));
+        assert!(Config::from_toml("[calendar]\ntimezone = \"Mars/Olympus\"").is_err());
+
+        let config = Config::from_toml("[calendar]\nday_starts_at = \"18:00\"").unwrap();
+        assert_eq!(
+            config.calendar.day_starts_at,
+            NaiveTime::from_hms_opt(18, 0, 0).unwrap()
+        );
+        let config = Config::from_toml("[calendar]\nday_starts_at = 06:30:00").unwrap();
+        assert_eq!(
+            config.calendar.day_starts_at,
+            NaiveTime::from_hms_opt(6, 30, 0).unwrap()
+        );
+        assert_eq!(Config::default().calendar.day_starts_at, NaiveTime::MIN);
+    }
+
+    #[test]
+    fn test_config_pay_period() {
+        let config = Config::from_toml(concat!(
+            "[calendar]\nfiscal_year_start = 10\n",
+            "pay_period = { kind = \"biweekly\", anchor = 2024-03-29 }\n",
+        ))
+        .unwrap();
+        assert_eq!(config.calendar.fiscal_year_start, 10);
+        assert_eq!(
+            config.calendar.pay_period,
+            Some(crate::period::PayPeriod::Biweekly {
+                anchor: NaiveDate::from_ymd_opt(2024, 3, 29).unwrap()
+            })
+        );
+        let config = Config::from_toml("[calendar.pay_period]\nkind = \"semi-monthly\"").unwrap();
+        assert_eq!(
+            config.calendar.pay_period,
+            Some(crate::period::PayPeriod::SemiMonthly)
+        );
+    }
+
+    #[test]
+    fn test_config_invalid() {
+        assert!(Config::from_toml("[invoice]\ntax_rate = \"high\"").is_err());
+    }
+}
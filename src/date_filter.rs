@@ -1,9 +1,30 @@
-use chrono::NaiveDate;
+use chrono::{DateTime, Datelike, FixedOffset, NaiveDate, NaiveTime, Weekday};
+use chrono_tz::Tz;
+
+use crate::period;
+
+/// Which days of the week match.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+enum DaysOfWeek {
+    #[default]
+    All,
+    Weekdays,
+    Weekends,
+}
 
 #[derive(Debug)]
 pub struct DateFilter {
     date_ranges: Vec<(NaiveDate, NaiveDate)>,
     dates: Vec<NaiveDate>,
+    days_of_week: DaysOfWeek,
+    // Sorted dates that never match
+    excluded: Vec<NaiveDate>,
+    // Entries are converted to this zone before matching
+    zone: Option<Tz>,
+    // When a workday begins
+    day_start: NaiveTime,
+    // Split entries that cross the start of a workday
+    split_days: bool,
 
     // The oldest and newest date from dates or date_ranges
     pub oldest_date: Option<NaiveDate>,
@@ -14,7 +35,8 @@ impl DateFilter {
     /// New DateFilter
     ///
     /// # Arguments
-    /// * date_ranges - range in pairs, but pair ordering is not important
+    /// * date_ranges - range in pairs, but pair ordering is not important.
+    ///   `NaiveDate::MIN` or `NaiveDate::MAX` leaves that end of a range open.
     /// * individual_dates - individual days
     ///
     /// # Returns
@@ -27,57 +49,182 @@ impl DateFilter {
     /// let filter = DateFilter::new(&range, &dates);
     ///
     pub fn new(date_ranges: &[NaiveDate], individual_dates: &[NaiveDate]) -> Self {
-        let mut oldest_date: Option<NaiveDate> = None;
-        let mut newest_date: Option<NaiveDate> = None;
-
-        // Process individual dates
-        let sorted_individual_dates = {
-            let mut sorting = individual_dates.to_vec();
-            sorting.sort();
-            sorting
-        };
-        // Get the oldest and newest so far
-        if !sorted_individual_dates.is_empty() {
-            oldest_date = sorted_individual_dates.first().cloned();
-            newest_date = sorted_individual_dates.last().cloned();
+        let mut spans: Vec<(NaiveDate, NaiveDate)> =
+            individual_dates.iter().map(|date| (*date, *date)).collect();
+        for pair in date_ranges.chunks_exact(2) {
+            let (start, end) = match pair {
+                [start, end] => (*start, *end),
+                _ => panic!("Invalid pair of dates"),
+            };
+            // Swap start and end if they are in the wrong order
+            spans.push((start.min(end), start.max(end)));
         }
+        Self::from_spans(spans)
+    }
 
-        let mut processed_ranges = Vec::new();
-        if !date_ranges.is_empty() {
-            for pair in date_ranges.chunks_exact(2) {
-                let (start, end) = match pair {
-                    [start, end] => (*start, *end),
-                    _ => panic!("Invalid pair of dates"),
-                };
-                // Swap start and end if they are in the wrong order
-                let (start, end) = if start > end {
-                    (end, start)
-                } else {
-                    (start, end)
-                };
+    /// New DateFilter from inclusive spans, which are sorted and merged.
+    fn from_spans(spans: Vec<(NaiveDate, NaiveDate)>) -> Self {
+        let merged = merge(spans);
+        let mut oldest_date = merged.first().map(|(first, _)| *first);
+        let newest_date = merged.last().map(|(_, last)| *last);
+        // Without a lower bound there is no early termination
+        if oldest_date == Some(NaiveDate::MIN) {
+            oldest_date = None;
+        }
 
-                processed_ranges.push((start, end));
-                newest_date = newest_date.map_or(Some(end), |newest| Some(newest.max(end)));
-            }
+        // A lone day is kept as a date and longer spans as ranges
+        let (dates, date_ranges): (Vec<_>, Vec<_>) =
+            merged.into_iter().partition(|(first, last)| first == last);
+        Self {
+            date_ranges,
+            dates: dates.into_iter().map(|(date, _)| date).collect(),
+            days_of_week: DaysOfWeek::All,
+            excluded: Vec::new(),
+            zone: None,
+            day_start: NaiveTime::MIN,
+            split_days: false,
+            oldest_date,
+            newest_date,
+        }
+    }
 
-            // Sort by the begin of each range
-            processed_ranges.sort_by(|a, b| a.0.cmp(&b.0));
+    /// Every date and range as sorted, non-overlapping, inclusive spans.
+    fn spans(&self) -> Vec<(NaiveDate, NaiveDate)> {
+        let dates = self.dates.iter().map(|date| (*date, *date));
+        merge(dates.chain(self.date_ranges.iter().cloned()).collect())
+    }
 
-            // Once sorted the processed_ranges oldest is obvious
-            if let Some((first_start, _)) = processed_ranges.first() {
-                oldest_date = match oldest_date {
-                    Some(current_oldest) if *first_start < current_oldest => Some(*first_start),
-                    None => Some(*first_start),
-                    _ => oldest_date,
-                };
+    /// Keep the weekday, holiday, zone, and workday settings of other.
+    fn with_settings_of(mut self, other: &Self) -> Self {
+        self.days_of_week = other.days_of_week;
+        self.excluded = other.excluded.clone();
+        self.zone = other.zone;
+        self.day_start = other.day_start;
+        self.split_days = other.split_days;
+        self
+    }
+
+    /// Dates in either filter.
+    ///
+    /// The weekday, holiday, and zone settings of self are kept.
+    pub fn union(&self, other: &Self) -> Self {
+        let mut spans = self.spans();
+        spans.extend(other.spans());
+        Self::from_spans(spans).with_settings_of(self)
+    }
+
+    /// Dates in both filters.
+    ///
+    /// The weekday, holiday, and zone settings of self are kept.
+    pub fn intersection(&self, other: &Self) -> Self {
+        let (ours, theirs) = (self.spans(), other.spans());
+        let mut spans = Vec::new();
+        let (mut i, mut j) = (0, 0);
+        while i < ours.len() && j < theirs.len() {
+            let first = ours[i].0.max(theirs[j].0);
+            let last = ours[i].1.min(theirs[j].1);
+            if first <= last {
+                spans.push((first, last));
+            }
+            // Advance whichever ends first
+            match ours[i].1 < theirs[j].1 {
+                true => i += 1,
+                false => j += 1,
             }
         }
-        Self {
-            date_ranges: processed_ranges,
-            dates: sorted_individual_dates,
-            oldest_date,
-            newest_date,
+        Self::from_spans(spans).with_settings_of(self)
+    }
+
+    /// Dates in self that are not in other, ie `--range a b --except c`.
+    ///
+    /// The weekday, holiday, and zone settings of self are kept.
+    pub fn difference(&self, other: &Self) -> Self {
+        let theirs = other.spans();
+        let mut spans = Vec::new();
+        for (first, last) in self.spans() {
+            let mut first = Some(first);
+            for (cut_first, cut_last) in theirs.iter() {
+                let Some(begin) = first.filter(|begin| *begin <= last) else {
+                    break;
+                };
+                if *cut_last < begin || *cut_first > last {
+                    continue;
+                }
+                if *cut_first > begin {
+                    spans.push((begin, cut_first.pred_opt().unwrap_or(begin)));
+                }
+                first = cut_last.succ_opt();
+            }
+            if let Some(begin) = first.filter(|begin| *begin <= last) {
+                spans.push((begin, last));
+            }
         }
+        Self::from_spans(spans).with_settings_of(self)
+    }
+
+    /// The matching dates from oldest to newest.
+    ///
+    /// Weekday and holiday settings apply. An open-ended filter goes on
+    /// for a very long time, so bound it first.
+    pub fn days(&self) -> impl Iterator<Item = NaiveDate> + '_ {
+        self.spans()
+            .into_iter()
+            .flat_map(|(first, last)| first.iter_days().take_while(move |date| *date <= last))
+            .filter(|date| self.contains(date))
+    }
+
+    /// Only match Monday through Friday
+    pub fn weekdays(mut self) -> Self {
+        self.days_of_week = DaysOfWeek::Weekdays;
+        self
+    }
+
+    /// Only match Saturday and Sunday
+    pub fn weekends(mut self) -> Self {
+        self.days_of_week = DaysOfWeek::Weekends;
+        self
+    }
+
+    /// Never match the given dates, ie holidays
+    pub fn excluding(mut self, dates: &[NaiveDate]) -> Self {
+        self.excluded.extend_from_slice(dates);
+        self.excluded.sort();
+        self.excluded.dedup();
+        self
+    }
+
+    /// Match entries by their date in the zone rather than as written
+    pub fn in_zone(mut self, zone: Tz) -> Self {
+        self.zone = Some(zone);
+        self
+    }
+
+    /// The zone entries are converted to, if any
+    pub fn zone(&self) -> Option<Tz> {
+        self.zone
+    }
+
+    /// Workdays begin at the time, ie 18:00 for a night shift
+    pub fn day_starts_at(mut self, time: NaiveTime) -> Self {
+        self.day_start = time;
+        self
+    }
+
+    /// Split entries crossing the start of a workday so each part matches
+    /// on its own day
+    pub fn split_days(mut self) -> Self {
+        self.split_days = true;
+        self
+    }
+
+    /// When entries are split, the time a workday begins
+    pub fn split_at(&self) -> Option<NaiveTime> {
+        self.split_days.then_some(self.day_start)
+    }
+
+    /// The workday of a timestamp already in the filter zone.
+    pub fn workday(&self, when: &DateTime<FixedOffset>) -> NaiveDate {
+        period::workday(when, self.day_start)
     }
 
     /// Are there any dates or ranges
@@ -99,14 +246,42 @@ impl DateFilter {
                 return false;
             }
         }
-        self.dates.contains(date)
-            || self
-                .date_ranges
-                .iter()
-                .any(|(begin, cease)| date >= begin && date <= cease)
+        let is_weekend = matches!(date.weekday(), Weekday::Sat | Weekday::Sun);
+        match self.days_of_week {
+            DaysOfWeek::Weekdays if is_weekend => return false,
+            DaysOfWeek::Weekends if !is_weekend => return false,
+            _ => {}
+        }
+        if self.excluded.binary_search(date).is_ok() {
+            return false;
+        }
+        if self.dates.binary_search(date).is_ok() {
+            return true;
+        }
+        // The last range beginning on or before the date
+        let after = self.date_ranges.partition_point(|(begin, _)| begin <= date);
+        after > 0 && date <= &self.date_ranges[after - 1].1
     }
 }
 
+/// Sort spans and merge any that overlap or are adjacent.
+fn merge(mut spans: Vec<(NaiveDate, NaiveDate)>) -> Vec<(NaiveDate, NaiveDate)> {
+    spans.sort();
+    let mut merged: Vec<(NaiveDate, NaiveDate)> = Vec::with_capacity(spans.len());
+    for (first, last) in spans {
+        // Overlapping or adjacent; nothing follows the end of time
+        let joins = |previous: &(NaiveDate, NaiveDate)| match previous.1.succ_opt() {
+            Some(next) => first <= next,
+            None => true,
+        };
+        match merged.last_mut() {
+            Some(previous) if joins(previous) => previous.1 = previous.1.max(last),
+            _ => merged.push((first, last)),
+        }
+    }
+    merged
+}
+
 #[cfg(test)]
 pub fn ymd(year: i32, month: u32, day: u32) -> NaiveDate {
     NaiveDate::from_ymd_opt(year, month, day).unwrap()
@@ -166,6 +341,117 @@ mod tests {
         assert_eq!(filter.contains(&ymd(2024, 2, 1)), false);
     }
 
+    #[test]
+    fn test_filter_open_ranges() {
+        let since = DateFilter::new(&[ymd(2024, 3, 1), NaiveDate::MAX], &[]);
+        assert_eq!(since.oldest_date, Some(ymd(2024, 3, 1)));
+        assert_eq!(since.contains(&ymd(2024, 2, 29)), false);
+        assert_eq!(since.contains(&ymd(2024, 3, 1)), true);
+        assert_eq!(since.contains(&ymd(2099, 1, 1)), true);
+
+        let until = DateFilter::new(&[NaiveDate::MIN, ymd(2024, 3, 1)], &[ymd(2024, 4, 1)]);
+        assert_eq!(until.oldest_date, None);
+        assert_eq!(until.newest_date, Some(ymd(2024, 4, 1)));
+        assert_eq!(until.contains(&ymd(1999, 1, 1)), true);
+        assert_eq!(until.contains(&ymd(2024, 3, 2)), false);
+        assert_eq!(until.contains(&ymd(2024, 4, 1)), true);
+    }
+
+    #[test]
+    fn test_filter_days_of_week() {
+        // Friday through Monday
+        let range = [ymd(2024, 3, 29), ymd(2024, 4, 1)];
+        let weekdays = DateFilter::new(&range, &[]).weekdays();
+        assert_eq!(weekdays.contains(&ymd(2024, 3, 29)), true);
+        assert_eq!(weekdays.contains(&ymd(2024, 3, 30)), false);
+        assert_eq!(weekdays.contains(&ymd(2024, 3, 31)), false);
+        assert_eq!(weekdays.contains(&ymd(2024, 4, 1)), true);
+
+        let weekends = DateFilter::new(&range, &[]).weekends();
+        assert_eq!(weekends.contains(&ymd(2024, 3, 29)), false);
+        assert_eq!(weekends.contains(&ymd(2024, 3, 30)), true);
+        assert_eq!(weekends.contains(&ymd(2024, 3, 31)), true);
+    }
+
+    #[test]
+    fn test_filter_excluding() {
+        let range = [ymd(2024, 3, 29), ymd(2024, 4, 1)];
+        let filter = DateFilter::new(&range, &[])
+            .excluding(&[ymd(2024, 4, 1)])
+            .excluding(&[ymd(2024, 3, 29)]);
+        assert_eq!(filter.contains(&ymd(2024, 3, 29)), false);
+        assert_eq!(filter.contains(&ymd(2024, 3, 30)), true);
+        assert_eq!(filter.contains(&ymd(2024, 4, 1)), false);
+    }
+
+    #[test]
+    fn test_filter_merges() {
+        let date_ranges = vec![
+            ymd(2024, 3, 5),
+            ymd(2024, 3, 1),
+            ymd(2024, 3, 4),
+            ymd(2024, 3, 10),
+            ymd(2024, 3, 12),
+            ymd(2024, 3, 15),
+        ];
+        let individual_dates = vec![ymd(2024, 3, 11), ymd(2024, 3, 3), ymd(2024, 3, 20)];
+        let filter = DateFilter::new(&date_ranges, &individual_dates);
+        assert_eq!(
+            filter.date_ranges,
+            vec![(ymd(2024, 3, 1), ymd(2024, 3, 15))]
+        );
+        assert_eq!(filter.dates, vec![ymd(2024, 3, 20)]);
+        assert_eq!(filter.contains(&ymd(2024, 3, 16)), false);
+        assert_eq!(filter.contains(&ymd(2024, 3, 20)), true);
+    }
+
+    #[test]
+    fn test_filter_set_algebra() {
+        let march = DateFilter::new(&[ymd(2024, 3, 1), ymd(2024, 3, 31)], &[]);
+        let week = DateFilter::new(&[ymd(2024, 3, 28), ymd(2024, 4, 3)], &[]);
+
+        let union = march.union(&week);
+        assert_eq!(union.date_ranges, vec![(ymd(2024, 3, 1), ymd(2024, 4, 3))]);
+
+        let intersection = march.intersection(&week);
+        assert_eq!(
+            intersection.date_ranges,
+            vec![(ymd(2024, 3, 28), ymd(2024, 3, 31))]
+        );
+        assert!(march
+            .intersection(&DateFilter::new(&[], &[ymd(2024, 4, 1)]))
+            .is_empty());
+
+        let except = DateFilter::new(&[ymd(2024, 3, 10), ymd(2024, 3, 20)], &[ymd(2024, 3, 2)]);
+        let difference = march.difference(&except);
+        assert_eq!(difference.dates, vec![ymd(2024, 3, 1)]);
+        assert_eq!(
+            difference.date_ranges,
+            vec![
+                (ymd(2024, 3, 3), ymd(2024, 3, 9)),
+                (ymd(2024, 3, 21), ymd(2024, 3, 31))
+            ]
+        );
+        assert!(march.difference(&march).is_empty());
+
+        let since = DateFilter::new(&[ymd(2024, 3, 1), NaiveDate::MAX], &[]);
+        let until_april =
+            since.difference(&DateFilter::new(&[ymd(2024, 4, 1), NaiveDate::MAX], &[]));
+        assert_eq!(until_april.date_ranges, march.date_ranges);
+    }
+
+    #[test]
+    fn test_filter_days() {
+        let filter = DateFilter::new(&[ymd(2024, 3, 29), ymd(2024, 4, 2)], &[ymd(2024, 4, 5)])
+            .weekdays()
+            .excluding(&[ymd(2024, 4, 1)]);
+        let days: Vec<NaiveDate> = filter.days().collect();
+        assert_eq!(
+            days,
+            vec![ymd(2024, 3, 29), ymd(2024, 4, 2), ymd(2024, 4, 5)]
+        );
+    }
+
     #[test]
     fn test_filter_both_ranges_and_dates() {
         let date_ranges = vec![
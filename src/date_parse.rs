@@ -1,16 +1,19 @@
 use crate::util_time::current_datetime;
-use chrono::{Datelike, Duration, NaiveDate};
+use chrono::{Datelike, Duration, Months, NaiveDate, Weekday};
+use idid::period::{self, Calendar};
 
-/// Create a vector of NaiveDate from strings
+/// Create a vector of inclusive date spans from strings
 ///
-/// See date_from_str for format details.
-pub fn strings_to_dates(dates: &Option<Vec<String>>) -> Result<Vec<NaiveDate>, String> {
-    // Process dates and ranges using str_to_date
-    let mut parsed: Vec<NaiveDate> = Vec::new();
+/// See span_from_str for format details.
+pub fn strings_to_spans(
+    dates: &Option<Vec<String>>,
+    calendar: &Calendar,
+) -> Result<Vec<(NaiveDate, NaiveDate)>, String> {
+    let mut parsed: Vec<(NaiveDate, NaiveDate)> = Vec::new();
     if let Some(vec) = dates {
         for date_str in vec {
-            match date_from_str(date_str) {
-                Ok(date) => parsed.push(date),
+            match span_from_str(date_str, calendar) {
+                Ok(span) => parsed.push(span),
                 Err(err) => return Err(format!("Invalid {}: {}", date_str, err)),
             }
         }
@@ -18,6 +21,196 @@ pub fn strings_to_dates(dates: &Option<Vec<String>>) -> Result<Vec<NaiveDate>, S
     Ok(parsed)
 }
 
+/// Create an inclusive span of dates relative to the current date.
+///
+/// # Arguments
+///
+/// * `format` - A string slice representing a period or a single date.
+///    - Days, weeks, months, or years ending today, ie "7d", "2w", "3m", "1y".
+///      "7d" is today and the six days before.
+///    - Any two of these joined by "..", ie "0401..0415" or "2024-01..2024-03".
+///      Without the second, ie "mon..", the range ends today.
+///    - "thisweek", "lastweek", "thismonth", "lastmonth", "thisquarter",
+///      "lastquarter", "thisyear", or "lastyear".
+///    - ISO week YYYY-Www, ie "2024-W14".
+///    - Month YYYY-MM, ie "2024-04".
+///    - Quarter YYYY-Qn, ie "2024-Q2".
+///    - Year YYYY, ie "2024".
+///    - Pay period "pp", or "pp1" for the one before, when configured.
+///    - Fiscal year "fy2025" or quarter "fy2025q1".
+///    - Any single date of date_from_str is a span of one day.
+/// * `calendar` - The week start, pay period, and fiscal year.
+pub fn span_from_str(format: &str, calendar: &Calendar) -> Result<(NaiveDate, NaiveDate), String> {
+    let lower_case = format.to_lowercase();
+    let now = today(calendar);
+    let invalid = || format!("invalid period: {}", format);
+
+    // Range of any two DATEs, ie "0401..0415", or to today, ie "mon.."
+    if let Some((begin, end)) = format.split_once("..") {
+        if begin.is_empty() {
+            return Err(format!("missing beginning of range: {}", format));
+        }
+        let begin = span_from_str(begin, calendar)?;
+        let end = match end {
+            "" => (now, now),
+            _ => span_from_str(end, calendar)?,
+        };
+        return Ok((begin.0.min(end.0), begin.1.max(end.1)));
+    }
+
+    if let Some(span) = recent_span(&lower_case, now)? {
+        return Ok(span);
+    }
+    if let Some(span) = configured_span(&lower_case, now, calendar)? {
+        return Ok(span);
+    }
+
+    let previous_day = |date: NaiveDate| date - Duration::days(1);
+    let named = match lower_case.as_str() {
+        "thisweek" => Some(period::week(now, calendar.week_start)),
+        "lastweek" => Some(period::week(now - Duration::days(7), calendar.week_start)),
+        "thismonth" => period::month(now.year(), now.month()),
+        "lastmonth" => {
+            let (first, _) = period::month(now.year(), now.month()).ok_or_else(invalid)?;
+            let last_month = previous_day(first);
+            period::month(last_month.year(), last_month.month())
+        }
+        "thisquarter" => period::quarter(now.year(), period::quarter_of(now)),
+        "lastquarter" => {
+            let (first, _) =
+                period::quarter(now.year(), period::quarter_of(now)).ok_or_else(invalid)?;
+            let last_quarter = previous_day(first);
+            period::quarter(last_quarter.year(), period::quarter_of(last_quarter))
+        }
+        "thisyear" => period::year(now.year()),
+        "lastyear" => period::year(now.year() - 1),
+        _ => None,
+    };
+    if let Some(span) = named {
+        return Ok(span);
+    }
+
+    let digits = |text: &str| !text.is_empty() && text.chars().all(|c| c.is_ascii_digit());
+    let number = |text: &str| text.parse::<u32>().map_err(|_| invalid());
+    if lower_case.len() >= 6
+        && lower_case.get(..4).is_some_and(digits)
+        && lower_case.get(4..5) == Some("-")
+    {
+        let year = number(&lower_case[..4])? as i32;
+        let rest = &lower_case[5..];
+        if let Some(week) = rest.strip_prefix('w').filter(|w| digits(w)) {
+            return period::iso_week(year, number(week)?).ok_or_else(invalid);
+        }
+        if let Some(quarter) = rest.strip_prefix('q').filter(|q| digits(q)) {
+            return period::quarter(year, number(quarter)?).ok_or_else(invalid);
+        }
+        if rest.len() == 2 && digits(rest) {
+            return period::month(year, number(rest)?).ok_or_else(invalid);
+        }
+    }
+    // A valid MMDD never starts with a month above 12
+    if lower_case.len() == 4 && digits(&lower_case) && number(&lower_case[..2])? > 12 {
+        return period::year(number(&lower_case)? as i32).ok_or_else(invalid);
+    }
+
+    let date = date_from_str(format, calendar)?;
+    Ok((date, date))
+}
+
+/// Span of days ending today, ie "7d", "2w", "3m", or "1y".
+///
+/// # Returns
+/// The span, None when the input is not a count with a unit, or the
+/// reason the count is invalid.
+fn recent_span(input: &str, today: NaiveDate) -> Result<Option<(NaiveDate, NaiveDate)>, String> {
+    let Some(unit) = input.chars().last().filter(|unit| "dwmy".contains(*unit)) else {
+        return Ok(None);
+    };
+    let count = &input[..input.len() - 1];
+    if count.is_empty() || !count.chars().all(|c| c.is_ascii_digit()) {
+        return Ok(None);
+    }
+    let count: u32 = count
+        .parse()
+        .map_err(|_| format!("invalid count: {}", input))?;
+    if count == 0 {
+        return Err(format!("invalid count: {}", input));
+    }
+
+    let invalid = || format!("invalid count: {}", input);
+    let days_ago =
+        |days: i64| Duration::try_days(days - 1).and_then(|days| today.checked_sub_signed(days));
+    let months_ago = |months: u32| {
+        today
+            .checked_sub_months(Months::new(months))
+            .and_then(|date| date.checked_add_signed(Duration::days(1)))
+    };
+    let first = match unit {
+        'd' => days_ago(i64::from(count)),
+        'w' => days_ago(i64::from(count) * 7),
+        'm' => months_ago(count),
+        _ => count.checked_mul(12).and_then(months_ago),
+    };
+    first.map(|first| Some((first, today))).ok_or_else(invalid)
+}
+
+/// Most pay periods "ppN" may go back, some 38 years of biweekly pay.
+const MOST_PAY_PERIODS_BACK: u32 = 1000;
+
+/// Pay period or fiscal span from the calendar configuration.
+///
+/// "pp" is the current pay period and "pp1" the one before it, up to "pp1000".
+/// "fy2025" is the fiscal year ending in 2025 and "fy2025q1" its first quarter.
+///
+/// # Returns
+/// The span, None when the input is not one of these, or the reason it is
+/// invalid.
+fn configured_span(
+    input: &str,
+    today: NaiveDate,
+    calendar: &Calendar,
+) -> Result<Option<(NaiveDate, NaiveDate)>, String> {
+    let digits = |text: &str| text.chars().all(|c| c.is_ascii_digit());
+    let invalid = || format!("invalid period: {}", input);
+
+    if let Some(back) = input.strip_prefix("pp").filter(|back| digits(back)) {
+        let back: u32 = match back {
+            "" => 0,
+            _ => back.parse().map_err(|_| invalid())?,
+        };
+        if back > MOST_PAY_PERIODS_BACK {
+            return Err(invalid());
+        }
+        let missing = || format!("{} needs a pay_period in the [calendar] config", input);
+        let mut span = calendar.pay_period(today).ok_or_else(missing)?;
+        for _ in 0..back {
+            span = span
+                .0
+                .pred_opt()
+                .and_then(|day| calendar.pay_period(day))
+                .ok_or_else(invalid)?;
+        }
+        return Ok(Some(span));
+    }
+
+    let Some(rest) = input.strip_prefix("fy") else {
+        return Ok(None);
+    };
+    let (year, quarter) = match rest.split_once('q') {
+        Some((year, quarter)) => (year, Some(quarter)),
+        None => (rest, None),
+    };
+    if year.len() != 4 || !digits(year) || quarter.is_some_and(|q| q.is_empty() || !digits(q)) {
+        return Ok(None);
+    }
+    let year: i32 = year.parse().map_err(|_| invalid())?;
+    let span = match quarter {
+        Some(quarter) => calendar.fiscal_quarter(year, quarter.parse().map_err(|_| invalid())?),
+        None => calendar.fiscal_year(year),
+    };
+    span.ok_or_else(invalid).map(Some)
+}
+
 /// Create a date relative to the current date and time.
 ///
 /// # Arguments
@@ -30,43 +223,197 @@ pub fn strings_to_dates(dates: &Option<Vec<String>>) -> Result<Vec<NaiveDate>, S
 ///    - Last MMDD (leading 0, dashes optinal); within 364ish days.
 ///    - Last week day with optional numeric suffix to add weeks.
 ///      "mon" is last Monday and "mon1" goes back an additional week.
+///    - An English phrase; see phrase_to_date.
+/// * `calendar` - The first day of the week for phrases like "end of week".
 ///
 /// # Panics
 ///
 /// This function does not panic under normal circumstances.
 /// However, if the input string represents a date outside the valid range of
 /// `NaiveDate`, it may panic when trying to create the date.
-pub fn date_from_str(format: &str) -> Result<NaiveDate, String> {
-    let now = current_datetime().date_naive();
-
-    #[cfg(debug_assertions)]
-    println!("format={:?}, now={}", format, now);
+pub fn date_from_str(format: &str, calendar: &Calendar) -> Result<NaiveDate, String> {
+    let now = today(calendar);
 
     // All days before today and YYYY-MM-DD variants
     if format.chars().all(|c| c.is_ascii_digit() || c == '-') {
-        let value = numeric_to_date(format, Some(now));
-        #[cfg(debug_assertions)]
-        println!("numeric_to_date: {:?}", value.as_ref().expect("date"));
-        return value;
+        return numeric_to_date(format, Some(now));
     }
 
     // Instead of forcing the full "yesterday", allow anything that starts
     // with "yester", like "yesternight".  This one is for you Cameron.
     let lower_case = format.to_lowercase();
     if lower_case.starts_with("yester") {
-        #[cfg(debug_assertions)]
-        println!("Working on 'yester'");
         return now
             .checked_sub_signed(chrono::Duration::days(1))
             .ok_or_else(|| format!("unable to get {} ", lower_case));
     }
 
+    // Keep the abbreviated week day grammar, including its errors, as is
+    let dow_form = lower_case.len() == 3
+        || (lower_case.is_char_boundary(3) && lower_case[3..].chars().all(|c| c.is_ascii_digit()));
     match format {
         "today" => Ok(now),
-        _ => last_dow(&lower_case, Some(now)),
+        _ if dow_form => last_dow(&lower_case, Some(now)),
+        _ => phrase_to_date(&lower_case, now, calendar),
     }
 }
 
+/// Forms suggested when a date cannot be parsed.
+const DATE_FORMS: &str = "today, yesterday, 3, 0401, 2024-04-01, fri, fri1, \
+    3 days ago, last friday, friday before last, beginning of month, \
+    first monday of april, april 1";
+
+/// Parse a forgiving English phrase to a date.
+///
+/// The input is expected to be lowercase.
+///  - "3 days ago", "2 weeks ago", "1 month ago", or "1 year ago".
+///  - "friday" and "last friday" are the same as "fri".
+///  - "friday before last" is the same as "fri1".
+///  - "beginning of month" or "end of week"; also quarter and year.
+///  - "first monday of april" in the last April; also second to fifth and last.
+///  - "april 1" or "1 april" is the same as "0401"; "april 1 2024" is exact.
+fn phrase_to_date(input: &str, today: NaiveDate, calendar: &Calendar) -> Result<NaiveDate, String> {
+    let unknown = || format!("unknown date {:?}; try: {}", input, DATE_FORMS);
+    let words: Vec<&str> = input
+        .split_whitespace()
+        .map(|word| word.trim_matches(','))
+        .filter(|word| !word.is_empty() && *word != "the")
+        .collect();
+
+    let date = match words.as_slice() {
+        [count, unit, "ago"] => {
+            let count: u32 = count.parse().map_err(|_| unknown())?;
+            match unit.trim_end_matches('s') {
+                "day" => today.checked_sub_signed(Duration::days(count.into())),
+                "week" => today.checked_sub_signed(Duration::weeks(count.into())),
+                "month" => today.checked_sub_months(Months::new(count)),
+                "year" => today.checked_sub_months(Months::new(count.saturating_mul(12))),
+                _ => return Err(unknown()),
+            }
+        }
+        [day] => {
+            let day = weekday_from_name(day).ok_or_else(unknown)?;
+            return last_dow(&abbreviation(day), Some(today));
+        }
+        ["last", day] => {
+            let day = weekday_from_name(day).ok_or_else(unknown)?;
+            return last_dow(&abbreviation(day), Some(today));
+        }
+        [day, "before", "last"] => {
+            let day = weekday_from_name(day).ok_or_else(unknown)?;
+            return last_dow(&format!("{}1", abbreviation(day)), Some(today));
+        }
+        [edge @ ("beginning" | "start" | "end"), "of", unit] => {
+            let (first, last) = match *unit {
+                "week" => Some(period::week(today, calendar.week_start)),
+                "month" => period::month(today.year(), today.month()),
+                "quarter" => period::quarter(today.year(), period::quarter_of(today)),
+                "year" => period::year(today.year()),
+                _ => None,
+            }
+            .ok_or_else(unknown)?;
+            Some(if *edge == "end" { last } else { first })
+        }
+        [ordinal, day, "of", month] => {
+            let day = weekday_from_name(day).ok_or_else(unknown)?;
+            let month = month_from_name(month).ok_or_else(unknown)?;
+            let nth = match *ordinal {
+                "first" | "1st" => Some(1),
+                "second" | "2nd" => Some(2),
+                "third" | "3rd" => Some(3),
+                "fourth" | "4th" => Some(4),
+                "fifth" | "5th" => Some(5),
+                "last" => None,
+                _ => return Err(unknown()),
+            };
+            let in_year = |year: i32| match nth {
+                Some(n) => NaiveDate::from_weekday_of_month_opt(year, month, day, n),
+                None => period::month(year, month).map(|(_, last)| {
+                    last - Duration::days(
+                        ((last.weekday().num_days_from_monday() + 7 - day.num_days_from_monday())
+                            % 7)
+                        .into(),
+                    )
+                }),
+            };
+            // The most recent, like MMDD
+            match in_year(today.year()) {
+                Some(date) if date <= today => Some(date),
+                _ => in_year(today.year() - 1),
+            }
+        }
+        [first, second] => {
+            let (month, day) = month_and_day(first, second).ok_or_else(unknown)?;
+            return numeric_to_date(&format!("{:02}{:02}", month, day), Some(today));
+        }
+        [first, second, year] => {
+            let (month, day) = month_and_day(first, second).ok_or_else(unknown)?;
+            let year: i32 = year.parse().map_err(|_| unknown())?;
+            NaiveDate::from_ymd_opt(year, month, day)
+        }
+        _ => return Err(unknown()),
+    };
+    date.ok_or_else(|| format!("invalid date: {}", input))
+}
+
+/// Month and day from "april 1", "1 april", or "apr 1st".
+fn month_and_day(first: &str, second: &str) -> Option<(u32, u32)> {
+    let day_number = |word: &str| {
+        word.trim_end_matches(|c: char| c.is_ascii_alphabetic())
+            .parse::<u32>()
+            .ok()
+    };
+    match (month_from_name(first), month_from_name(second)) {
+        (Some(month), None) => Some((month, day_number(second)?)),
+        (None, Some(month)) => Some((month, day_number(first)?)),
+        _ => None,
+    }
+}
+
+/// Week day from at least three letters of its name, ie "fri" or "friday".
+fn weekday_from_name(word: &str) -> Option<Weekday> {
+    let names = [
+        "monday",
+        "tuesday",
+        "wednesday",
+        "thursday",
+        "friday",
+        "saturday",
+        "sunday",
+    ];
+    let index = names
+        .iter()
+        .position(|name| word.len() >= 3 && name.starts_with(word))?;
+    Weekday::try_from(index as u8).ok()
+}
+
+/// Month 1 to 12 from at least three letters of its name, ie "apr" or "april".
+fn month_from_name(word: &str) -> Option<u32> {
+    let names = [
+        "january",
+        "february",
+        "march",
+        "april",
+        "may",
+        "june",
+        "july",
+        "august",
+        "september",
+        "october",
+        "november",
+        "december",
+    ];
+    names
+        .iter()
+        .position(|name| word.len() >= 3 && name.starts_with(word))
+        .map(|index| index as u32 + 1)
+}
+
+/// The lowercase abbreviation used by last_dow, ie "fri".
+fn abbreviation(day: Weekday) -> String {
+    day.to_string().to_lowercase()
+}
+
 /// Parse a string to a date
 ///
 /// # Arguments
@@ -187,6 +534,11 @@ fn last_dow(input: &str, reference_date: Option<NaiveDate>) -> Result<NaiveDate,
     Ok(today - Duration::days(days_ago.into()))
 }
 
+/// The current workday in the calendar timezone, or locally without one.
+pub fn today(calendar: &Calendar) -> NaiveDate {
+    calendar.workday(current_datetime())
+}
+
 fn local_naive_date() -> NaiveDate {
     current_datetime().date_naive()
 }
@@ -241,7 +593,7 @@ mod tests {
     ) {
         let expected = NaiveDate::from_ymd_opt(year, month, day).unwrap();
         set_current_datetime_to_april_1_2024();
-        let parsed_date = date_from_str(input);
+        let parsed_date = date_from_str(input, &Calendar::default());
         current_datetime_reset();
         match parsed_date {
             Ok(actual) => {
@@ -327,7 +679,7 @@ mod tests {
     #[case("tue1", 19)]
     fn test_date_parse_dow(#[case] input: &str, #[case] dom: u32) {
         set_current_datetime_to_april_1_2024(); // monday
-        let actual = date_from_str(input).unwrap();
+        let actual = date_from_str(input, &Calendar::default()).unwrap();
         current_datetime_reset();
         let expected = NaiveDate::from_ymd_opt(2024, 3, dom).unwrap();
         assert_eq!(
@@ -338,10 +690,195 @@ mod tests {
         assert_eq!(expected, actual);
     }
 
+    #[rstest] // periods relative to Monday 2024-04-01
+    #[case("thisweek", (2024, 4, 1), (2024, 4, 7))]
+    #[case("lastweek", (2024, 3, 25), (2024, 3, 31))]
+    #[case("thismonth", (2024, 4, 1), (2024, 4, 30))]
+    #[case("lastmonth", (2024, 3, 1), (2024, 3, 31))]
+    #[case("thisquarter", (2024, 4, 1), (2024, 6, 30))]
+    #[case("lastquarter", (2024, 1, 1), (2024, 3, 31))]
+    #[case("lastyear", (2023, 1, 1), (2023, 12, 31))]
+    #[case("2024-W14", (2024, 4, 1), (2024, 4, 7))]
+    #[case("2024-04", (2024, 4, 1), (2024, 4, 30))]
+    #[case("2024-Q1", (2024, 1, 1), (2024, 3, 31))]
+    #[case("2024", (2024, 1, 1), (2024, 12, 31))]
+    #[case("0331", (2024, 3, 31), (2024, 3, 31))]
+    #[case("00", (2024, 4, 1), (2024, 4, 1))]
+    #[case("1d", (2024, 4, 1), (2024, 4, 1))]
+    #[case("7d", (2024, 3, 26), (2024, 4, 1))]
+    #[case("2w", (2024, 3, 19), (2024, 4, 1))]
+    #[case("3m", (2024, 1, 2), (2024, 4, 1))]
+    #[case("1y", (2023, 4, 2), (2024, 4, 1))]
+    #[case("0301..0315", (2024, 3, 1), (2024, 3, 15))]
+    #[case("0315..0301", (2024, 3, 1), (2024, 3, 15))]
+    #[case("mon..", (2024, 3, 25), (2024, 4, 1))]
+    #[case("2024-01..2024-02", (2024, 1, 1), (2024, 2, 29))]
+    #[case("fri", (2024, 3, 29), (2024, 3, 29))]
+    fn test_span_from_str(
+        #[case] input: &str,
+        #[case] first: (i32, u32, u32),
+        #[case] last: (i32, u32, u32),
+    ) {
+        let ymd = |(y, m, d): (i32, u32, u32)| NaiveDate::from_ymd_opt(y, m, d).unwrap();
+        set_current_datetime_to_april_1_2024();
+        let actual = span_from_str(input, &Calendar::default());
+        current_datetime_reset();
+        assert_eq!(actual, Ok((ymd(first), ymd(last))), "input={:?}", input);
+    }
+
+    #[test]
+    fn test_span_from_str_week_start() {
+        let calendar = Calendar {
+            week_start: chrono::Weekday::Sun,
+            ..Calendar::default()
+        };
+        set_current_datetime_to_april_1_2024();
+        let actual = span_from_str("thisweek", &calendar);
+        current_datetime_reset();
+        assert_eq!(
+            actual,
+            Ok((
+                NaiveDate::from_ymd_opt(2024, 3, 31).unwrap(),
+                NaiveDate::from_ymd_opt(2024, 4, 6).unwrap()
+            ))
+        );
+    }
+
+    #[rstest]
+    #[case("pp", (2024, 3, 29), (2024, 4, 11))]
+    #[case("pp1", (2024, 3, 15), (2024, 3, 28))]
+    #[case("PP2", (2024, 3, 1), (2024, 3, 14))]
+    #[case("fy2024", (2023, 10, 1), (2024, 9, 30))]
+    #[case("fy2024q3", (2024, 4, 1), (2024, 6, 30))]
+    #[case("fy2025q1..pp", (2024, 3, 29), (2024, 12, 31))]
+    #[case("pp1000", (1985, 11, 29), (1985, 12, 12))]
+    fn test_span_from_str_configured(
+        #[case] input: &str,
+        #[case] first: (i32, u32, u32),
+        #[case] last: (i32, u32, u32),
+    ) {
+        let ymd = |(y, m, d): (i32, u32, u32)| NaiveDate::from_ymd_opt(y, m, d).unwrap();
+        let calendar = Calendar {
+            pay_period: Some(idid::period::PayPeriod::Biweekly {
+                anchor: ymd((2024, 1, 5)),
+            }),
+            fiscal_year_start: 10,
+            ..Calendar::default()
+        };
+        set_current_datetime_to_april_1_2024();
+        let actual = span_from_str(input, &calendar);
+        current_datetime_reset();
+        assert_eq!(actual, Ok((ymd(first), ymd(last))), "input={:?}", input);
+    }
+
+    #[rstest]
+    #[case("pp1001")]
+    #[case("pp99999999")]
+    fn test_span_from_str_pay_period_too_far(#[case] input: &str) {
+        let calendar = Calendar {
+            pay_period: Some(idid::period::PayPeriod::Weekly),
+            ..Calendar::default()
+        };
+        assert_eq!(
+            span_from_str(input, &calendar),
+            Err(format!("invalid period: {}", input))
+        );
+    }
+
+    #[test]
+    fn test_today_in_timezone() {
+        let calendar = Calendar {
+            timezone: Some(chrono_tz::Pacific::Honolulu),
+            ..Calendar::default()
+        };
+        set_current_datetime_to_april_1_2024();
+        let local = today(&Calendar::default());
+        let honolulu = today(&calendar);
+        current_datetime_reset();
+        assert_eq!(local, NaiveDate::from_ymd_opt(2024, 4, 1).unwrap());
+        assert_eq!(honolulu, NaiveDate::from_ymd_opt(2024, 3, 31).unwrap());
+    }
+
+    #[rstest]
+    #[case("2024-W54", "invalid period: 2024-W54")]
+    #[case("2024-Q5", "invalid period: 2024-Q5")]
+    #[case("2024-13", "invalid period: 2024-13")]
+    #[case("0d", "invalid count: 0d")]
+    #[case("99999999d", "invalid count: 99999999d")]
+    #[case("99999999w", "invalid count: 99999999w")]
+    #[case("99999999m", "invalid count: 99999999m")]
+    #[case("999999999y", "invalid count: 999999999y")]
+    #[case("..0401", "missing beginning of range: ..0401")]
+    #[case("pp", "pp needs a pay_period in the [calendar] config")]
+    #[case("fy2024q5", "invalid period: fy2024q5")]
+    #[case(
+        "abcé-de",
+        concat!(
+            "unknown date \"abcé-de\"; try: today, yesterday, 3, 0401, 2024-04-01, ",
+            "fri, fri1, 3 days ago, last friday, friday before last, beginning of month, ",
+            "first monday of april, april 1"
+        )
+    )]
+    fn test_span_from_str_bad_input(#[case] input: &str, #[case] expected: &str) {
+        assert_eq!(
+            span_from_str(input, &Calendar::default()),
+            Err(expected.to_string())
+        );
+    }
+
+    #[rstest] // phrases relative to Monday 2024-04-01
+    #[case("3 days ago", 2024, 3, 29)]
+    #[case("1 week ago", 2024, 3, 25)]
+    #[case("2 months ago", 2024, 2, 1)]
+    #[case("last friday", 2024, 3, 29)]
+    #[case("last Monday", 2024, 3, 25)]
+    #[case("friday before last", 2024, 3, 22)]
+    #[case("beginning of month", 2024, 4, 1)]
+    #[case("end of the quarter", 2024, 6, 30)]
+    #[case("start of year", 2024, 1, 1)]
+    #[case("first monday of april", 2024, 4, 1)]
+    #[case("second tue of april", 2023, 4, 11)]
+    #[case("last friday of march", 2024, 3, 29)]
+    #[case("april 1", 2023, 4, 1)]
+    #[case("march 15th", 2024, 3, 15)]
+    #[case("15 Mar", 2024, 3, 15)]
+    #[case("april 1, 2024", 2024, 4, 1)]
+    #[case("friday", 2024, 3, 29)]
+    fn test_date_parse_phrase(
+        #[case] input: &str,
+        #[case] year: i32,
+        #[case] month: u32,
+        #[case] day: u32,
+    ) {
+        set_current_datetime_to_april_1_2024();
+        let actual = date_from_str(input, &Calendar::default());
+        current_datetime_reset();
+        let expected = NaiveDate::from_ymd_opt(year, month, day).unwrap();
+        assert_eq!(Ok(expected), actual, "input={:?}", input);
+    }
+
+    #[rstest]
+    #[case("3 fortnights ago")]
+    #[case("next friday")]
+    #[case("someday")]
+    #[case("beginning of decade")]
+    fn test_date_parse_phrase_bad_input(#[case] input: &str) {
+        set_current_datetime_to_april_1_2024();
+        let actual = date_from_str(input, &Calendar::default());
+        current_datetime_reset();
+        let error = actual.expect_err(input);
+        assert!(
+            error.contains("try: today, yesterday"),
+            "input={:?}, error={:?}",
+            input,
+            error
+        );
+    }
+
     #[test]
     fn test_date_parse_dow_bad_input() {
         assert_eq!(
-            date_from_str("xyz"),
+            date_from_str("xyz", &Calendar::default()),
             Err(
                 "invalid day of the week abbreviation; use: mon, tue, wed, thu, fri, sat, sun"
                     .to_string()
@@ -1,5 +1,6 @@
 use crate::date_filter::DateFilter;
-use chrono::{DateTime, FixedOffset};
+use chrono::{DateTime, Duration, FixedOffset, NaiveTime, TimeZone};
+use chrono_tz::Tz;
 use rev_lines::RevLines;
 use std::fmt;
 use std::fs;
@@ -39,28 +40,56 @@ impl Entry {
         hh_mm(&self.duration())
     }
 
+    /// Words in the text starting with "+" or "@", ie "+project" or "@WIP".
+    pub fn tags(&self) -> Vec<&str> {
+        self.text
+            .split_whitespace()
+            .filter(|word| word.len() > 1 && (word.starts_with('+') || word.starts_with('@')))
+            .collect()
+    }
+
+    /// Split into parts at the start of each workday, oldest first.
+    ///
+    /// With day_start of midnight, 23:30 to 00:45 becomes 23:30 to 00:00
+    /// and 00:00 to 00:45. Boundaries are in the offset of begin.
+    pub fn split_days(&self, day_start: NaiveTime) -> Vec<Entry> {
+        let shift = day_start - NaiveTime::MIN;
+        let offset = *self.begin.offset();
+        let mut parts = Vec::new();
+        let mut begin = self.begin;
+        while begin < self.cease {
+            let next_day = (begin - shift).date_naive() + Duration::days(1);
+            let boundary = offset
+                .from_local_datetime(&next_day.and_time(day_start))
+                .single()
+                .expect("fixed offsets are never ambiguous");
+            let cease = boundary.min(self.cease);
+            parts.push(Entry {
+                begin,
+                cease,
+                text: self.text.clone(),
+            });
+            begin = cease;
+        }
+        parts
+    }
+
     /// Serialize as json or TSV
     pub fn serialize(&self, in_seconds: &bool, json: bool) -> String {
-        let (label, value) = match in_seconds {
-            true => ("seconds", format!("{}", self.duration().num_seconds())),
-            false => ("duration", format!("\"{}\"", self.hh_mm())),
-        };
         if json {
-            format!(
-                "{{\"begin\":\"{}\",\"{}\":{},\"text\":\"{}\"}}",
-                self.begin.to_rfc3339(),
-                label,
-                value,
-                escape_for_json(&self.text)
-            )
-        } else {
-            format!(
-                "{}\t{}\t{}",
-                self.begin.to_rfc3339(),
-                value.trim_start_matches('"').trim_end_matches('"'),
-                self.text
-            )
+            return serde_json::to_string(&crate::json::Record::from(self))
+                .expect("Entry serializes to JSON");
         }
+        self.serialize_duration(&self.duration(), in_seconds)
+    }
+
+    /// Serialize as TSV showing a duration other than its own, ie rounded
+    pub fn serialize_duration(&self, duration: &Duration, in_seconds: &bool) -> String {
+        let value = match in_seconds {
+            true => format!("{}", duration.num_seconds()),
+            false => hh_mm(duration),
+        };
+        format!("{}\t{}\t{}", self.begin.to_rfc3339(), value, self.text)
     }
 
     /// Get timestamp and text from a tab-separated value (TSV) line.
@@ -107,6 +136,16 @@ where
 
     // rfc 3339\tText for what I did at this time
     last_line: Option<(DateTime<FixedOffset>, String)>,
+
+    // Convert timestamps to this zone instead of the offset written
+    zone: Option<Tz>,
+
+    // Split entries at the start of each workday
+    split_at: Option<NaiveTime>,
+    // Parts of a split entry waiting to be returned, newest last
+    pending: Vec<Entry>,
+    // Nothing older can match, after pending
+    finished: bool,
 }
 
 /// # Arguments
@@ -114,6 +153,8 @@ where
 /// filter: Does the entry match.
 /// oldest: The oldest date allowed for early termination.
 /// last_line: The timestamp and text from previous entry.
+/// zone: Convert timestamps to this zone before filtering.
+/// split_at: Split entries at this start of each workday before filtering.
 ///
 /// # Example
 /// use idid::entry;
@@ -125,6 +166,10 @@ where
 ///     filter: |_| true,
 ///     oldest: None,
 ///     last_line: None,
+///     zone: None,
+///     split_at: None,
+///     pending: Vec::new(),
+///     finished: false,
 /// };
 ///
 /// You can also use a DateFilter.
@@ -177,8 +222,27 @@ where
             last_line: None,
             lines: RevLines::new(source),
             line_from_end: 0,
+            zone: None,
+            split_at: None,
+            pending: Vec::new(),
+            finished: false,
         }
     }
+
+    /// Split entries crossing the start of a workday, when given, and
+    /// filter each part on its own.
+    pub fn with_split_at(mut self, day_start: Option<NaiveTime>) -> Self {
+        self.split_at = day_start;
+        self
+    }
+
+    /// Convert each timestamp to the zone, when given, before filtering.
+    ///
+    /// Without a zone, timestamps keep the offset they were written with.
+    pub fn with_zone(mut self, zone: Option<Tz>) -> Self {
+        self.zone = zone;
+        self
+    }
 }
 
 impl<F, R> Iterator for EntryIterator<F, R>
@@ -189,6 +253,12 @@ where
     type Item = Entry;
     /// Get the next matching entry.
     fn next(&mut self) -> Option<Self::Item> {
+        if let Some(part) = self.pending.pop() {
+            return Some(part);
+        }
+        if self.finished {
+            return None;
+        }
         // while let Some(line) = self.lines.next() {
         for line in self.lines.by_ref() {
             self.line_from_end += 1;
@@ -199,12 +269,19 @@ where
             let (when, text) = Entry::from_tsv(&line)
                 .map_err(|e| format!("TSV #{} from end: {}", self.line_from_end, e))
                 .unwrap_or_else(|err_msg| panic!("{}", err_msg));
+            let when = match self.zone {
+                Some(zone) => when.with_timezone(&zone).fixed_offset(),
+                None => when,
+            };
 
             // Older than oldest? Nothing more to find.
-            if self.oldest.is_some() && when.date_naive() < self.oldest? {
+            let older = self.oldest.is_some_and(|oldest| when.date_naive() < oldest);
+            if older && self.split_at.is_none() {
                 // println!("finished: when={} >= oldest={:?}", when, self.oldest);
                 return None;
             }
+            // Except the newer part of an entry beginning here
+            self.finished = older;
 
             // Create entry from last line and this line rfc3339 as beginning
             let some_entry = match self.last_line {
@@ -227,12 +304,25 @@ where
             if some_entry.is_some() {
                 let entry = some_entry.unwrap();
 
-                if (self.filter)(&entry) {
+                if let Some(day_start) = self.split_at {
+                    let filter = &mut self.filter;
+                    self.pending = entry
+                        .split_days(day_start)
+                        .into_iter()
+                        .filter(|part| filter(part))
+                        .collect();
+                    if let Some(part) = self.pending.pop() {
+                        return Some(part);
+                    }
+                } else if (self.filter)(&entry) {
                     // println!("  acceptable: true");
                     return Some(entry);
                 }
                 // println!("  acceptable: false  {:?}", entry);
             }
+            if self.finished {
+                return None;
+            }
         }
         // println!("  acceptable: False");
         None
@@ -273,9 +363,11 @@ pub fn pick(
         Box::new(io::BufReader::new(file))
     };
 
-    let filter_func = move |entry: &Entry| filter.contains(&entry.begin.date_naive());
+    let filter_func = move |entry: &Entry| filter.contains(&filter.workday(&entry.begin));
 
     EntryIterator::new(buf_reader, filter_func, filter.oldest_date)
+        .with_zone(filter.zone())
+        .with_split_at(filter.split_at())
 }
 
 pub fn hh_mm(duration: &chrono::Duration) -> String {
@@ -285,21 +377,6 @@ pub fn hh_mm(duration: &chrono::Duration) -> String {
     format!("{:02}:{:02}", hours, minutes)
 }
 
-fn escape_for_json(text: &str) -> String {
-    let mut escaped_string = String::new();
-    for c in text.chars() {
-        match c {
-            '"' => escaped_string.push_str("\\\""),
-            '\\' => escaped_string.push_str("\\\\"),
-            '\n' => escaped_string.push_str("\\n"),
-            '\r' => escaped_string.push_str("\\r"),
-            '\t' => escaped_string.push_str("\\t"),
-            _ => escaped_string.push(c),
-        }
-    }
-    escaped_string
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -312,6 +389,17 @@ mod tests {
         assert_eq!(entry.1, "Sample text");
     }
 
+    #[test]
+    fn test_entry_tags() {
+        let (begin, _) = Entry::from_tsv("2024-04-01T12:00:00+00:00\t").unwrap();
+        let entry = Entry {
+            begin,
+            cease: begin,
+            text: "+acme helped @CJ with a + sign".to_string(),
+        };
+        assert_eq!(entry.tags(), vec!["+acme", "@CJ"]);
+    }
+
     #[test]
     fn test_pick_iterator_empty() {
         let mut iterator = EntryIterator {
@@ -320,6 +408,10 @@ mod tests {
             last_line: None,
             lines: RevLines::new(io::Cursor::new(Vec::new())),
             line_from_end: 0,
+            zone: None,
+            split_at: None,
+            pending: Vec::new(),
+            finished: false,
         };
         let next_value = iterator.next();
         // println!("next={:?}", next_value);
@@ -350,6 +442,95 @@ mod tests {
         assert_eq!(5, actual);
     }
 
+    #[test]
+    fn test_pick_with_datefilter_open() {
+        let since = DateFilter::new(&[ymd(2024, 3, 28), chrono::NaiveDate::MAX], &[]);
+        let texts: Vec<String> = pick(sample_week(), &since).map(|e| e.text).collect();
+        assert_eq!(texts, vec!["Friday", "Thursday"]);
+
+        let until = DateFilter::new(&[chrono::NaiveDate::MIN, ymd(2024, 3, 26)], &[]);
+        let texts: Vec<String> = pick(sample_week(), &until).map(|e| e.text).collect();
+        assert_eq!(texts, vec!["Tuesday", "Monday"]);
+    }
+
+    #[test]
+    fn test_pick_with_zone() {
+        // Late on the 28th in UTC is already the 29th in Tokyo
+        let source = concat!(
+            "2024-03-28T20:00:00Z\t*~*~*--------------------\n",
+            "2024-03-28T22:00:00Z\tLate\n",
+        );
+        let filter = DateFilter::new(&[], &[ymd(2024, 3, 29)]);
+        assert_eq!(pick(source, &filter).count(), 0);
+
+        let filter = filter.in_zone(chrono_tz::Asia::Tokyo);
+        let entries: Vec<Entry> = pick(source, &filter).collect();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].begin.to_rfc3339(), "2024-03-29T05:00:00+09:00");
+    }
+
+    #[test]
+    fn test_pick_with_day_start() {
+        // The night shift of the 28th
+        let source = concat!(
+            "2024-03-28T18:00:00Z\t*~*~*--------------------\n",
+            "2024-03-28T23:00:00Z\tEvening\n",
+            "2024-03-29T05:00:00Z\tMorning\n",
+            "2024-03-29T18:00:00Z\t*~*~*--------------------\n",
+            "2024-03-29T19:00:00Z\tNext shift\n",
+        );
+        let filter = DateFilter::new(&[], &[ymd(2024, 3, 28)])
+            .day_starts_at(chrono::NaiveTime::from_hms_opt(18, 0, 0).unwrap());
+        let texts: Vec<String> = pick(source, &filter).map(|e| e.text).collect();
+        assert_eq!(texts, vec!["Morning", "Evening"]);
+    }
+
+    #[test]
+    fn test_entry_split_days() {
+        let source = concat!(
+            "2024-03-28T22:00:00-05:00\t*~*~*--------------------\n",
+            "2024-03-30T00:45:00-05:00\tLong night\n",
+        );
+        let entry = pick(source, &DateFilter::new(&[], &[ymd(2024, 3, 28)]))
+            .next()
+            .unwrap();
+        let parts: Vec<String> = entry
+            .split_days(NaiveTime::MIN)
+            .iter()
+            .map(|part| format!("{} {}", part.begin.to_rfc3339(), part.hh_mm()))
+            .collect();
+        assert_eq!(
+            parts,
+            vec![
+                "2024-03-28T22:00:00-05:00 02:00",
+                "2024-03-29T00:00:00-05:00 24:00",
+                "2024-03-30T00:00:00-05:00 00:45",
+            ]
+        );
+        let night = NaiveTime::from_hms_opt(18, 0, 0).unwrap();
+        assert_eq!(entry.split_days(night).len(), 2);
+    }
+
+    #[test]
+    fn test_pick_split_days() {
+        let source = concat!(
+            "2024-03-28T23:30:00Z\t*~*~*--------------------\n",
+            "2024-03-29T00:45:00Z\tLate\n",
+            "2024-03-29T01:00:00Z\tLater\n",
+        );
+        let filter = DateFilter::new(&[], &[ymd(2024, 3, 29)]);
+        let texts: Vec<String> = pick(source, &filter).map(|e| e.hh_mm()).collect();
+        assert_eq!(texts, vec!["00:15"]);
+
+        let filter = filter.split_days();
+        let texts: Vec<String> = pick(source, &filter).map(|e| e.hh_mm()).collect();
+        assert_eq!(texts, vec!["00:15", "00:45"]);
+
+        let filter = DateFilter::new(&[], &[ymd(2024, 3, 28)]).split_days();
+        let texts: Vec<String> = pick(source, &filter).map(|e| e.hh_mm()).collect();
+        assert_eq!(texts, vec!["00:30"]);
+    }
+
     // Two entries
     fn sample_simple() -> String {
         concat!(
@@ -0,0 +1,114 @@
+use crate::entry::Entry;
+use crate::period::Calendar;
+use chrono::{DateTime, FixedOffset};
+
+/// Write entries as an Org outline with CLOCK lines.
+///
+/// Each workday is a heading with an entry sub-heading carrying the clock
+/// interval, so org-clock reports and agenda views can use the entries.
+///
+/// # Arguments
+/// * `entries` - Entries in any order; they are written oldest first.
+/// * `calendar` - Decides the workday of each entry, as for `show`.
+///
+/// # Example
+/// ```text
+/// * 2024-04-01 Mon
+/// ** +messages
+///    CLOCK: [2024-04-01 Mon 08:00]--[2024-04-01 Mon 08:10] =>  0:10
+/// ```
+pub fn org(entries: &[Entry], calendar: &Calendar) -> String {
+    let mut sorted: Vec<&Entry> = entries.iter().collect();
+    sorted.sort_by_key(|entry| entry.begin);
+
+    let mut outline = String::new();
+    let mut current_day = None;
+    for entry in sorted {
+        let day = calendar.workday(entry.begin);
+        if current_day != Some(day) {
+            outline.push_str(&format!("* {}\n", day.format("%Y-%m-%d %a")));
+            current_day = Some(day);
+        }
+        let minutes = entry.duration().num_minutes();
+        outline.push_str(&format!(
+            "** {}\n   CLOCK: {}--{} => {:>2}:{:02}\n",
+            entry.text,
+            org_timestamp(&entry.begin),
+            org_timestamp(&entry.cease),
+            minutes / 60,
+            minutes % 60
+        ));
+    }
+    outline
+}
+
+/// Inactive Org timestamp, ie "[2024-04-01 Mon 08:00]".
+fn org_timestamp(when: &DateTime<FixedOffset>) -> String {
+    when.format("[%Y-%m-%d %a %H:%M]").to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(begin: &str, cease: &str, text: &str) -> Entry {
+        Entry {
+            begin: DateTime::parse_from_rfc3339(begin).unwrap(),
+            cease: DateTime::parse_from_rfc3339(cease).unwrap(),
+            text: text.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_org() {
+        // Newest first as given by pick
+        let entries = vec![
+            entry(
+                "2024-04-02T09:00:00Z",
+                "2024-04-02T11:30:00Z",
+                "+acme review",
+            ),
+            entry(
+                "2024-04-01T08:10:00Z",
+                "2024-04-01T08:15:00Z",
+                "day planning",
+            ),
+            entry("2024-04-01T08:00:00Z", "2024-04-01T08:10:00Z", "+messages"),
+        ];
+        assert_eq!(
+            org(&entries, &Calendar::default()),
+            concat!(
+                "* 2024-04-01 Mon\n",
+                "** +messages\n",
+                "   CLOCK: [2024-04-01 Mon 08:00]--[2024-04-01 Mon 08:10] =>  0:10\n",
+                "** day planning\n",
+                "   CLOCK: [2024-04-01 Mon 08:10]--[2024-04-01 Mon 08:15] =>  0:05\n",
+                "* 2024-04-02 Tue\n",
+                "** +acme review\n",
+                "   CLOCK: [2024-04-02 Tue 09:00]--[2024-04-02 Tue 11:30] =>  2:30\n",
+            )
+        );
+    }
+
+    #[test]
+    fn test_org_day_starts_at() {
+        let calendar = Calendar {
+            day_starts_at: chrono::NaiveTime::from_hms_opt(4, 0, 0).unwrap(),
+            ..Calendar::default()
+        };
+        let entries = vec![
+            entry("2024-04-02T00:30:00Z", "2024-04-02T01:00:00Z", "+ops page"),
+            entry(
+                "2024-04-01T22:00:00Z",
+                "2024-04-02T00:30:00Z",
+                "+ops deploy",
+            ),
+        ];
+        let outline = org(&entries, &calendar);
+        let headings: Vec<&str> = outline
+            .lines()
+            .filter(|line| line.starts_with("* "))
+            .collect();
+        assert_eq!(headings, vec!["* 2024-04-01 Mon"]);
+    }
+}
@@ -0,0 +1,255 @@
+use chrono::{Datelike, Duration, NaiveDate, Weekday};
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+use crate::entry::{hh_mm, Entry};
+use crate::period::Calendar;
+
+/// Shading from no time to the most, with '·' for a day without time.
+const SHADES: [char; 5] = ['·', '░', '▒', '▓', '█'];
+const ASCII_SHADES: [char; 5] = ['.', ':', 'o', 'O', '@'];
+/// GitHub's contribution greens.
+const COLORS: [&str; 5] = ["#ebedf0", "#9be9a8", "#40c463", "#30a14e", "#216e39"];
+/// SVG cell size and the space between cells.
+const CELL: usize = 11;
+const STEP: usize = 13;
+
+/// Tracked time per day of one year, laid out a week per column.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Heatmap {
+    pub year: i32,
+    pub week_start: Weekday,
+    pub days: BTreeMap<NaiveDate, Duration>,
+}
+
+impl Heatmap {
+    /// Total the entries by workday, keeping the days in the year.
+    pub fn new(year: i32, entries: &[Entry], calendar: &Calendar) -> Self {
+        let mut days = BTreeMap::new();
+        for entry in entries {
+            let day = calendar.workday(entry.begin);
+            if day.year() == year {
+                *days.entry(day).or_insert_with(Duration::zero) += entry.duration();
+            }
+        }
+        Self {
+            year,
+            week_start: calendar.week_start,
+            days,
+        }
+    }
+
+    /// The first day of the first column, on or before January 1st.
+    fn first_day(&self) -> NaiveDate {
+        let january = NaiveDate::from_ymd_opt(self.year, 1, 1).expect("valid year");
+        january.week(self.week_start).first_day()
+    }
+
+    /// 53 columns, or 54 when the year touches both ends of a week.
+    fn columns(&self) -> usize {
+        let december = NaiveDate::from_ymd_opt(self.year, 12, 31).expect("valid year");
+        (december - self.first_day()).num_days() as usize / 7 + 1
+    }
+
+    /// The day in a row and column, when it is in the year.
+    fn day(&self, row: usize, column: usize) -> Option<NaiveDate> {
+        let day = self.first_day() + Duration::days((column * 7 + row) as i64);
+        (day.year() == self.year).then_some(day)
+    }
+
+    fn most(&self) -> Duration {
+        self.days
+            .values()
+            .max()
+            .copied()
+            .unwrap_or_else(Duration::zero)
+    }
+
+    /// Intensity 0 for no time up to 4 for the longest day.
+    fn level(&self, day: NaiveDate) -> usize {
+        let most = self.most().num_seconds();
+        match self.days.get(&day).map(|d| d.num_seconds()) {
+            Some(seconds) if seconds > 0 && most > 0 => {
+                ((seconds * 4 + most - 1) / most).clamp(1, 4) as usize
+            }
+            _ => 0,
+        }
+    }
+
+    /// Month names over their first week and a row per weekday.
+    ///
+    /// Days outside the year are blank.
+    pub fn text(&self, unicode: bool) -> String {
+        let shades = match unicode {
+            true => SHADES,
+            false => ASCII_SHADES,
+        };
+        let columns = self.columns();
+        let mut months = vec![' '; columns];
+        for month in 1..=12 {
+            let first = NaiveDate::from_ymd_opt(self.year, month, 1).expect("valid month");
+            let column = (first - self.first_day()).num_days() as usize / 7;
+            let name = first.format("%b").to_string();
+            if months[column..].iter().take(4).all(|c| *c == ' ') {
+                for (offset, c) in name.chars().enumerate() {
+                    if let Some(slot) = months.get_mut(column + offset) {
+                        *slot = c;
+                    }
+                }
+            }
+        }
+
+        let mut text = format!("    {}", months.iter().collect::<String>());
+        text = text.trim_end().to_string();
+        text.push('\n');
+        for row in 0..7 {
+            let weekday = self.first_day() + Duration::days(row as i64);
+            let cells: String = (0..columns)
+                .map(|column| match self.day(row, column) {
+                    Some(day) => shades[self.level(day)],
+                    None => ' ',
+                })
+                .collect();
+            let _ = writeln!(text, "{} {}", weekday.format("%a"), cells.trim_end());
+        }
+        let legend: String = shades.iter().collect();
+        let _ = writeln!(
+            text,
+            "    Less {} More; most {}",
+            legend,
+            hh_mm(&self.most())
+        );
+        text
+    }
+
+    /// An SVG image with a square per day and its time as a tooltip.
+    pub fn svg(&self) -> String {
+        let (left, top) = (32, 20);
+        let width = left + self.columns() * STEP;
+        let height = top + 7 * STEP;
+        let mut svg = format!(
+            concat!(
+                "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" ",
+                "font-family=\"sans-serif\" font-size=\"10\">\n"
+            ),
+            width, height
+        );
+        for month in 1..=12 {
+            let first = NaiveDate::from_ymd_opt(self.year, month, 1).expect("valid month");
+            let column = (first - self.first_day()).num_days() as usize / 7;
+            let _ = writeln!(
+                svg,
+                "<text x=\"{}\" y=\"{}\">{}</text>",
+                left + column * STEP,
+                top - 6,
+                first.format("%b")
+            );
+        }
+        for row in (1..7).step_by(2) {
+            let weekday = self.first_day() + Duration::days(row as i64);
+            let _ = writeln!(
+                svg,
+                "<text x=\"0\" y=\"{}\">{}</text>",
+                top + row * STEP + CELL - 1,
+                weekday.format("%a")
+            );
+        }
+        for column in 0..self.columns() {
+            for row in 0..7 {
+                let Some(day) = self.day(row, column) else {
+                    continue;
+                };
+                let time = self.days.get(&day).copied().unwrap_or_else(Duration::zero);
+                let _ = writeln!(
+                    svg,
+                    concat!(
+                        "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" rx=\"2\" fill=\"{}\">",
+                        "<title>{} {}</title></rect>"
+                    ),
+                    left + column * STEP,
+                    top + row * STEP,
+                    CELL,
+                    CELL,
+                    COLORS[self.level(day)],
+                    day,
+                    hh_mm(&time)
+                );
+            }
+        }
+        svg.push_str("</svg>\n");
+        svg
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::date_filter::ymd;
+    use chrono::DateTime;
+    use rstest::rstest;
+
+    fn entry(begin: &str, minutes: i64) -> Entry {
+        let begin = DateTime::parse_from_rfc3339(begin).unwrap();
+        Entry {
+            begin,
+            cease: begin + Duration::minutes(minutes),
+            text: "work".to_string(),
+        }
+    }
+
+    fn heatmap() -> Heatmap {
+        let entries = vec![
+            entry("2024-01-01T09:00:00Z", 480),
+            entry("2024-01-02T09:00:00Z", 60),
+            entry("2024-01-03T09:00:00Z", 240),
+            entry("2023-12-31T09:00:00Z", 600),
+        ];
+        Heatmap::new(2024, &entries, &Calendar::default())
+    }
+
+    #[rstest]
+    #[case(2024, Weekday::Mon, 53)]
+    #[case(2023, Weekday::Mon, 53)]
+    #[case(2000, Weekday::Mon, 53)]
+    #[case(2000, Weekday::Sun, 54)]
+    fn test_columns(#[case] year: i32, #[case] week_start: Weekday, #[case] expected: usize) {
+        let heatmap = Heatmap {
+            year,
+            week_start,
+            days: BTreeMap::new(),
+        };
+        assert_eq!(heatmap.columns(), expected);
+    }
+
+    #[test]
+    fn test_level() {
+        let heatmap = heatmap();
+        assert_eq!(heatmap.days.len(), 3);
+        assert_eq!(heatmap.level(ymd(2024, 1, 1)), 4);
+        assert_eq!(heatmap.level(ymd(2024, 1, 2)), 1);
+        assert_eq!(heatmap.level(ymd(2024, 1, 3)), 2);
+        assert_eq!(heatmap.level(ymd(2024, 1, 4)), 0);
+    }
+
+    #[test]
+    fn test_text() {
+        let text = heatmap().text(true);
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines.len(), 9);
+        assert!(lines[0].starts_with("    Jan"));
+        assert!(lines[1].starts_with("Mon █·"));
+        assert!(lines[2].starts_with("Tue ░·"));
+        assert!(lines[3].starts_with("Wed ▒·"));
+        assert_eq!(lines[8], "    Less ·░▒▓█ More; most 08:00");
+        assert!(heatmap().text(false).contains("Mon @."));
+    }
+
+    #[test]
+    fn test_svg() {
+        let svg = heatmap().svg();
+        assert!(svg.starts_with("<svg "));
+        assert_eq!(svg.matches("<rect ").count(), 366);
+        assert!(svg.contains("fill=\"#216e39\"><title>2024-01-01 08:00</title>"));
+        assert!(svg.ends_with("</svg>\n"));
+    }
+}
@@ -0,0 +1,266 @@
+use chrono::{Datelike, NaiveDate};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::config::config_dir;
+
+/// Find a holidays file, trying the config directory for relative paths.
+///
+/// # Arguments
+/// * `path` - Used as given when absolute or when it exists; otherwise
+///   relative to the idid config directory, ie ~/.config/idid/holidays.ics
+pub fn resolve(path: &Path) -> PathBuf {
+    if path.is_absolute() || path.exists() {
+        return path.to_path_buf();
+    }
+    config_dir()
+        .map(|dir| dir.join(path))
+        .filter(|candidate| candidate.exists())
+        .unwrap_or_else(|| path.to_path_buf())
+}
+
+/// Read holidays from a file of dates or an iCalendar `.ics` file.
+///
+/// # Returns
+/// The sorted dates without duplicates.
+pub fn load(path: &Path) -> Result<Vec<NaiveDate>, String> {
+    let path = resolve(path);
+    let content = fs::read_to_string(&path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    let is_ics = path
+        .extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("ics"));
+    let parsed = if is_ics {
+        from_ics(&content)
+    } else {
+        from_text(&content)
+    };
+    parsed.map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+/// Dates one per line as YYYY-MM-DD, optionally followed by a name.
+///
+/// Blank lines and lines starting with '#' are ignored.
+///
+/// # Example
+/// ```text
+/// # 2024
+/// 2024-12-25 Christmas
+/// 2024-12-26
+/// ```
+pub fn from_text(content: &str) -> Result<Vec<NaiveDate>, String> {
+    let mut dates = Vec::new();
+    for (index, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let word = line.split_whitespace().next().unwrap_or_default();
+        let date = NaiveDate::parse_from_str(word, "%Y-%m-%d")
+            .map_err(|e| format!("line {}: invalid date {:?}: {}", index + 1, word, e))?;
+        dates.push(date);
+    }
+    Ok(sorted(dates))
+}
+
+/// Years a yearly event without COUNT or UNTIL repeats for.
+const YEARS_REPEATED: i32 = 100;
+
+/// Every day of every event in an iCalendar file.
+///
+/// Both `DTSTART;VALUE=DATE:20241225` and `DTSTART:20241225T000000Z`
+/// count as the 25th.  An event lasts from DTSTART up to, but not
+/// including, DTEND.  An RRULE may only be FREQ=YEARLY with an optional
+/// COUNT or UNTIL; without either it repeats for 100 years.
+pub fn from_ics(content: &str) -> Result<Vec<NaiveDate>, String> {
+    let mut dates = Vec::new();
+    let mut event: Option<Event> = None;
+    for line in content.lines() {
+        let line = line.trim();
+        match line {
+            "BEGIN:VEVENT" => event = Some(Event::default()),
+            "END:VEVENT" => {
+                if let Some(event) = event.take() {
+                    dates.extend(event.dates()?);
+                }
+            }
+            _ => {
+                let Some(event) = event.as_mut() else {
+                    continue;
+                };
+                // Parameters come before the colon, ie DTSTART;VALUE=DATE:20241225
+                let Some((name, value)) = line.split_once(':') else {
+                    continue;
+                };
+                match name.split(';').next().unwrap_or_default() {
+                    "DTSTART" => event.start = Some(ics_date("DTSTART", value)?),
+                    "DTEND" => event.end = Some(ics_date("DTEND", value)?),
+                    "RRULE" => event.rule = Some(value.to_string()),
+                    _ => {}
+                }
+            }
+        }
+    }
+    Ok(sorted(dates))
+}
+
+/// The parts of a VEVENT that decide its days.
+#[derive(Default)]
+struct Event {
+    start: Option<NaiveDate>,
+    end: Option<NaiveDate>,
+    rule: Option<String>,
+}
+
+impl Event {
+    fn dates(&self) -> Result<Vec<NaiveDate>, String> {
+        let start = self.start.ok_or("VEVENT without DTSTART")?;
+        let days = match self.end {
+            Some(end) if end > start => (end - start).num_days() as usize,
+            _ => 1,
+        };
+        let starts = match &self.rule {
+            Some(rule) => yearly(start, rule)?,
+            None => vec![start],
+        };
+        Ok(starts
+            .into_iter()
+            .flat_map(|first| first.iter_days().take(days))
+            .collect())
+    }
+}
+
+/// The starts of a yearly event, skipping years without the date, ie Feb 29.
+fn yearly(start: NaiveDate, rule: &str) -> Result<Vec<NaiveDate>, String> {
+    let unsupported = || {
+        format!(
+            "unsupported RRULE {:?}; use FREQ=YEARLY with COUNT or UNTIL",
+            rule
+        )
+    };
+    let mut is_yearly = false;
+    let mut count = usize::MAX;
+    let mut until = NaiveDate::MAX;
+    for part in rule.split(';') {
+        match part.split_once('=') {
+            Some(("FREQ", "YEARLY")) => is_yearly = true,
+            Some(("COUNT", number)) => count = number.parse().map_err(|_| unsupported())?,
+            Some(("UNTIL", date)) => until = ics_date("UNTIL", date)?,
+            _ => return Err(unsupported()),
+        }
+    }
+    if !is_yearly {
+        return Err(unsupported());
+    }
+    Ok((start.year()..=start.year() + YEARS_REPEATED)
+        .filter_map(|year| start.with_year(year))
+        .take_while(|date| *date <= until)
+        .take(count)
+        .collect())
+}
+
+/// The date of an iCalendar DATE or DATE-TIME value.
+fn ics_date(name: &str, value: &str) -> Result<NaiveDate, String> {
+    let day = value.get(..8).unwrap_or(value);
+    NaiveDate::parse_from_str(day, "%Y%m%d")
+        .map_err(|e| format!("invalid {} {:?}: {}", name, value, e))
+}
+
+fn sorted(mut dates: Vec<NaiveDate>) -> Vec<NaiveDate> {
+    dates.sort();
+    dates.dedup();
+    dates
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::date_filter::ymd;
+
+    #[test]
+    fn test_from_text() {
+        let content = "# winter\n2024-12-26\n\n2024-12-25 Christmas Day\n2024-12-25\n";
+        assert_eq!(
+            from_text(content),
+            Ok(vec![ymd(2024, 12, 25), ymd(2024, 12, 26)])
+        );
+        assert!(from_text("Christmas").unwrap_err().starts_with("line 1:"));
+    }
+
+    #[test]
+    fn test_from_ics() {
+        let content = concat!(
+            "BEGIN:VCALENDAR\r\n",
+            "BEGIN:VEVENT\r\n",
+            "DTSTART;VALUE=DATE:20240704\r\n",
+            "DTEND;VALUE=DATE:20240705\r\n",
+            "SUMMARY:Independence Day\r\n",
+            "END:VEVENT\r\n",
+            "BEGIN:VEVENT\r\n",
+            "DTSTART:20240101T000000Z\r\n",
+            "END:VEVENT\r\n",
+            "END:VCALENDAR\r\n",
+        );
+        assert_eq!(
+            from_ics(content),
+            Ok(vec![ymd(2024, 1, 1), ymd(2024, 7, 4)])
+        );
+        assert!(from_ics("BEGIN:VEVENT\nDTSTART:July\nEND:VEVENT\n").is_err());
+    }
+
+    #[test]
+    fn test_from_ics_days() {
+        let content = concat!(
+            "BEGIN:VEVENT\r\n",
+            "DTSTART;VALUE=DATE:20241224\r\n",
+            "DTEND;VALUE=DATE:20241227\r\n",
+            "SUMMARY:Winter break\r\n",
+            "END:VEVENT\r\n",
+        );
+        assert_eq!(
+            from_ics(content),
+            Ok(vec![
+                ymd(2024, 12, 24),
+                ymd(2024, 12, 25),
+                ymd(2024, 12, 26)
+            ])
+        );
+    }
+
+    #[test]
+    fn test_from_ics_yearly() {
+        let event = |rule: &str| {
+            format!(
+                "BEGIN:VEVENT\nDTSTART;VALUE=DATE:20231225\nRRULE:{}\nEND:VEVENT\n",
+                rule
+            )
+        };
+        assert_eq!(
+            from_ics(&event("FREQ=YEARLY;COUNT=2")),
+            Ok(vec![ymd(2023, 12, 25), ymd(2024, 12, 25)])
+        );
+        assert_eq!(
+            from_ics(&event("FREQ=YEARLY;UNTIL=20251231")),
+            Ok(vec![
+                ymd(2023, 12, 25),
+                ymd(2024, 12, 25),
+                ymd(2025, 12, 25)
+            ])
+        );
+        assert_eq!(from_ics(&event("FREQ=YEARLY")).unwrap().len(), 101);
+        assert!(from_ics(&event("FREQ=WEEKLY;BYDAY=MO")).is_err());
+        assert!(from_ics(&event("FREQ=YEARLY;BYMONTH=12")).is_err());
+    }
+
+    #[test]
+    fn test_load() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("holidays.ICS");
+        fs::write(
+            &path,
+            "BEGIN:VEVENT\nDTSTART;VALUE=DATE:20241225\nEND:VEVENT\n",
+        )
+        .unwrap();
+        assert_eq!(load(&path), Ok(vec![ymd(2024, 12, 25)]));
+        assert!(load(&dir.path().join("missing.txt")).is_err());
+    }
+}
@@ -0,0 +1,414 @@
+use crate::entry::{Entry, EntryIterator, START_RECORDING};
+use crate::tsv::format_line;
+use chrono::{DateTime, FixedOffset, Local, NaiveDate, NaiveDateTime, NaiveTime, TimeZone};
+use std::io;
+
+/// Time trackers with a CSV export we can read.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Source {
+    Toggl,
+    Clockify,
+}
+
+/// A span of time recorded by another tracker.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Interval {
+    pub begin: DateTime<FixedOffset>,
+    pub cease: DateTime<FixedOffset>,
+    pub text: String,
+}
+
+/// Read intervals from a Toggl or Clockify CSV export.
+///
+/// Exports have no UTC offset, so times are taken as local time.
+///
+/// # Arguments
+/// * `reader` - The CSV export including the header row.
+/// * `source` - Which tracker created the export.
+///
+/// # Returns
+/// The intervals in file order or the reason the file could not be read.
+pub fn from_csv(reader: impl io::Read, source: Source) -> Result<Vec<Interval>, String> {
+    read_csv(reader, source, &Local)
+}
+
+fn read_csv<Tz: TimeZone>(
+    reader: impl io::Read,
+    source: Source,
+    tz: &Tz,
+) -> Result<Vec<Interval>, String> {
+    let mut csv_reader = csv::ReaderBuilder::new().flexible(true).from_reader(reader);
+    let headers = csv_reader
+        .headers()
+        .map_err(|e| format!("{:?} CSV header: {}", source, e))?
+        .clone();
+
+    // Toggl and Clockify use the same column names, only the case differs
+    let column = |name: &str| {
+        headers
+            .iter()
+            .position(|h| h.trim().eq_ignore_ascii_case(name))
+            .ok_or_else(|| format!("{:?} CSV is missing column {:?}", source, name))
+    };
+    let start_date = column("Start date")?;
+    let start_time = column("Start time")?;
+    let end_date = column("End date")?;
+    let end_time = column("End time")?;
+    let description = column("Description")?;
+    let project = column("Project").ok();
+    let tags = column("Tags").ok();
+
+    let mut intervals = Vec::new();
+    for (row, record) in csv_reader.records().enumerate() {
+        // Header is line 1
+        let line = row + 2;
+        let record = record.map_err(|e| format!("CSV line {}: {}", line, e))?;
+        let field = |index: usize| record.get(index).unwrap_or_default().trim();
+        let optional = |index: Option<usize>| index.map(field).unwrap_or_default();
+
+        let begin = local_datetime(field(start_date), field(start_time), tz)
+            .map_err(|e| format!("CSV line {}: {}", line, e))?;
+        let cease = local_datetime(field(end_date), field(end_time), tz)
+            .map_err(|e| format!("CSV line {}: {}", line, e))?;
+        if cease < begin {
+            return Err(format!("CSV line {}: ends before it starts", line));
+        }
+
+        intervals.push(Interval {
+            begin,
+            cease,
+            text: compose_text(field(description), optional(project), optional(tags)),
+        });
+    }
+    Ok(intervals)
+}
+
+/// Read intervals from Watson's `frames` JSON file.
+///
+/// Each frame is `[start, stop, project, id, tags, updated]` with the
+/// times in seconds since the epoch.  The project becomes a `+project` tag.
+///
+/// # Arguments
+/// * `reader` - The contents of the `frames` file.
+///
+/// # Returns
+/// The intervals in file order or the reason the file could not be read.
+pub fn from_watson(reader: impl io::Read) -> Result<Vec<Interval>, String> {
+    read_watson(reader, &Local)
+}
+
+fn read_watson<Tz: TimeZone>(reader: impl io::Read, tz: &Tz) -> Result<Vec<Interval>, String> {
+    let frames: serde_json::Value =
+        serde_json::from_reader(reader).map_err(|e| format!("Watson frames: {}", e))?;
+    let frames = frames
+        .as_array()
+        .ok_or_else(|| "Watson frames: expected an array of frames".to_string())?;
+
+    let mut intervals = Vec::new();
+    for (index, frame) in frames.iter().enumerate() {
+        let invalid = |what: &str| format!("Watson frame #{}: invalid {}", index + 1, what);
+        let timestamp = |position: usize, what: &str| {
+            frame
+                .get(position)
+                .and_then(serde_json::Value::as_i64)
+                .and_then(|seconds| tz.timestamp_opt(seconds, 0).single())
+                .map(|when| when.fixed_offset())
+                .ok_or_else(|| invalid(what))
+        };
+        let begin = timestamp(0, "start")?;
+        let cease = timestamp(1, "stop")?;
+        if cease < begin {
+            return Err(format!(
+                "Watson frame #{}: stops before it starts",
+                index + 1
+            ));
+        }
+        let project = frame
+            .get(2)
+            .and_then(serde_json::Value::as_str)
+            .ok_or_else(|| invalid("project"))?;
+        let tags: Vec<&str> = match frame.get(4) {
+            Some(serde_json::Value::Array(tags)) => {
+                tags.iter().filter_map(serde_json::Value::as_str).collect()
+            }
+            _ => Vec::new(),
+        };
+
+        intervals.push(Interval {
+            begin,
+            cease,
+            text: compose_text("", project, &tags.join(",")),
+        });
+    }
+    Ok(intervals)
+}
+
+/// Combine date and time columns in one of the export formats.
+fn local_datetime<Tz: TimeZone>(
+    date: &str,
+    time: &str,
+    tz: &Tz,
+) -> Result<DateTime<FixedOffset>, String> {
+    let date = ["%Y-%m-%d", "%m/%d/%Y", "%d.%m.%Y"]
+        .iter()
+        .find_map(|format| NaiveDate::parse_from_str(date, format).ok())
+        .ok_or_else(|| format!("invalid date: {:?}", date))?;
+    let time = ["%H:%M:%S", "%H:%M", "%I:%M:%S %p", "%I:%M %p"]
+        .iter()
+        .find_map(|format| NaiveTime::parse_from_str(time, format).ok())
+        .ok_or_else(|| format!("invalid time: {:?}", time))?;
+    tz.from_local_datetime(&NaiveDateTime::new(date, time))
+        .earliest()
+        .map(|when| when.fixed_offset())
+        .ok_or_else(|| format!("no such local time: {} {}", date, time))
+}
+
+/// Build entry text as "+project description @tag".
+fn compose_text(description: &str, project: &str, tags: &str) -> String {
+    let mut words: Vec<String> = Vec::new();
+    if !project.is_empty() {
+        words.push(tag_word('+', project));
+    }
+    if !description.is_empty() {
+        words.push(description.split_whitespace().collect::<Vec<_>>().join(" "));
+    }
+    words.extend(
+        tags.split(',')
+            .map(str::trim)
+            .filter(|t| !t.is_empty())
+            .map(|t| tag_word('@', t)),
+    );
+    words.join(" ")
+}
+
+/// Make a single word tag from a possibly multi-word name.
+pub fn tag_word(prefix: char, name: &str) -> String {
+    format!(
+        "{}{}",
+        prefix,
+        name.split_whitespace().collect::<Vec<_>>().join("-")
+    )
+}
+
+/// Convert intervals into timestamped TSV lines.
+///
+/// Each interval becomes a line at its cease time.  A start marker is
+/// added whenever an interval does not begin where the previous one ceased,
+/// as idid derives the duration from the previous line.
+///
+/// # Returns
+/// The TSV lines in chronological order and a description of each
+/// overlapping or non-contiguous interval.
+pub fn to_lines(intervals: &[Interval]) -> (Vec<(DateTime<FixedOffset>, String)>, Vec<String>) {
+    let mut sorted = intervals.to_vec();
+    sorted.sort_by_key(|interval| interval.begin);
+
+    let mut lines = Vec::new();
+    let mut issues = Vec::new();
+    let mut previous: Option<&Interval> = None;
+    for interval in &sorted {
+        match previous {
+            Some(prior) if interval.begin < prior.cease => {
+                issues.push(format!(
+                    "overlap: {} {:?} begins before {} {:?} ends",
+                    interval.begin.to_rfc3339(),
+                    interval.text,
+                    prior.cease.to_rfc3339(),
+                    prior.text
+                ));
+            }
+            Some(prior) if interval.begin == prior.cease => {}
+            _ => {
+                if let Some(prior) = previous {
+                    issues.push(format!(
+                        "gap: {} to {} has no entry",
+                        prior.cease.to_rfc3339(),
+                        interval.begin.to_rfc3339()
+                    ));
+                }
+                lines.push((interval.begin, START_RECORDING.to_string()));
+            }
+        }
+        lines.push((interval.cease, interval.text.clone()));
+        // A contained interval must not shorten the span to compare against
+        match previous {
+            Some(prior) if interval.cease < prior.cease => {}
+            _ => previous = Some(interval),
+        }
+    }
+    (lines, issues)
+}
+
+/// Entries the TSV lines produce, as `idid show` would list them.
+///
+/// # Returns
+/// The entries from newest to oldest.
+pub fn preview(lines: &[(DateTime<FixedOffset>, String)]) -> Vec<Entry> {
+    let tsv: Vec<String> = lines
+        .iter()
+        .map(|(timestamp, text)| format_line(timestamp, text))
+        .collect();
+    EntryIterator::new(io::Cursor::new(tsv.join("\n")), |_: &Entry| true, None).collect()
+}
+
+/// Find intervals overlapping entries already in the journal.
+///
+/// # Arguments
+/// * `journal` - Entries in any order that do not overlap each other, as
+///   `idid check` ensures.
+///
+/// # Returns
+/// A description of each overlap.
+pub fn journal_overlaps(intervals: &[Interval], journal: &[Entry]) -> Vec<String> {
+    let mut oldest_first: Vec<&Entry> = journal.iter().collect();
+    oldest_first.sort_by_key(|entry| entry.begin);
+
+    let mut overlaps = Vec::new();
+    for interval in intervals {
+        // The first entry ending after the interval begins
+        let first = oldest_first.partition_point(|entry| entry.cease <= interval.begin);
+        for entry in oldest_first[first..]
+            .iter()
+            .take_while(|entry| entry.begin < interval.cease)
+        {
+            overlaps.push(format!(
+                "overlap: {} {:?} overlaps journal {} {:?}",
+                interval.begin.to_rfc3339(),
+                interval.text,
+                entry.begin.to_rfc3339(),
+                entry.text
+            ));
+        }
+    }
+    overlaps
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn utc() -> FixedOffset {
+        FixedOffset::east_opt(0).unwrap()
+    }
+
+    fn at(rfc3339: &str) -> DateTime<FixedOffset> {
+        DateTime::parse_from_rfc3339(rfc3339).unwrap()
+    }
+
+    #[test]
+    fn test_read_toggl() {
+        let csv = concat!(
+            "User,Email,Client,Project,Task,Description,Billable,Start date,Start time,End date,End time,Duration,Tags\n",
+            "Sam,sam@example.com,Acme,Acme Corp,,planning,Yes,2024-04-01,08:00:00,2024-04-01,08:10:00,00:10:00,\"call, urgent\"\n",
+        );
+        let intervals = read_csv(csv.as_bytes(), Source::Toggl, &utc()).unwrap();
+        assert_eq!(1, intervals.len());
+        assert_eq!(intervals[0].begin, at("2024-04-01T08:00:00Z"));
+        assert_eq!(intervals[0].cease, at("2024-04-01T08:10:00Z"));
+        assert_eq!(intervals[0].text, "+Acme-Corp planning @call @urgent");
+    }
+
+    #[test]
+    fn test_read_clockify() {
+        let csv = concat!(
+            "Project,Client,Description,Task,User,Tags,Billable,Start Date,Start Time,End Date,End Time\n",
+            "acme,,fixed issue #42,,Sam,,Yes,04/01/2024,09:50:00 AM,04/01/2024,01:30:00 PM\n",
+        );
+        let intervals = read_csv(csv.as_bytes(), Source::Clockify, &utc()).unwrap();
+        assert_eq!(intervals[0].begin, at("2024-04-01T09:50:00Z"));
+        assert_eq!(intervals[0].cease, at("2024-04-01T13:30:00Z"));
+        assert_eq!(intervals[0].text, "+acme fixed issue #42");
+    }
+
+    #[test]
+    fn test_read_missing_column() {
+        let csv = "Description,Start date\nplanning,2024-04-01\n";
+        let error = read_csv(csv.as_bytes(), Source::Toggl, &utc()).unwrap_err();
+        assert_eq!(error, "Toggl CSV is missing column \"Start time\"");
+    }
+
+    #[test]
+    fn test_read_watson() {
+        let frames = r#"[
+            [1711958400, 1711959000, "acme", "3b9e", ["call", "urgent"], 1711959000],
+            [1711959000, 1711962600, "home office", "7c1f", [], 1711962600]
+        ]"#;
+        let intervals = read_watson(frames.as_bytes(), &utc()).unwrap();
+        assert_eq!(2, intervals.len());
+        assert_eq!(intervals[0].begin, at("2024-04-01T08:00:00Z"));
+        assert_eq!(intervals[0].cease, at("2024-04-01T08:10:00Z"));
+        assert_eq!(intervals[0].text, "+acme @call @urgent");
+        assert_eq!(intervals[1].text, "+home-office");
+    }
+
+    #[test]
+    fn test_preview() {
+        let frames = r#"[[1711958400, 1711959000, "acme", "3b9e", [], 1711959000]]"#;
+        let intervals = read_watson(frames.as_bytes(), &utc()).unwrap();
+        let (lines, _) = to_lines(&intervals);
+        let entries = preview(&lines);
+        assert_eq!(1, entries.len());
+        assert_eq!("00:10", entries[0].hh_mm());
+        assert_eq!("+acme", entries[0].text);
+    }
+
+    #[test]
+    fn test_to_lines_gap_and_overlap() {
+        let interval = |begin: &str, cease: &str, text: &str| Interval {
+            begin: at(begin),
+            cease: at(cease),
+            text: text.to_string(),
+        };
+        let intervals = vec![
+            interval("2024-04-01T09:00:00Z", "2024-04-01T09:30:00Z", "second"),
+            interval("2024-04-01T08:00:00Z", "2024-04-01T09:00:00Z", "first"),
+            interval("2024-04-01T10:00:00Z", "2024-04-01T11:00:00Z", "third"),
+            interval("2024-04-01T10:30:00Z", "2024-04-01T11:30:00Z", "fourth"),
+        ];
+        let (lines, issues) = to_lines(&intervals);
+        let texts: Vec<&str> = lines.iter().map(|(_, text)| text.as_str()).collect();
+        assert_eq!(
+            texts,
+            vec![
+                START_RECORDING,
+                "first",
+                "second",
+                START_RECORDING,
+                "third",
+                "fourth"
+            ]
+        );
+        assert_eq!(2, issues.len());
+        assert!(issues[0].starts_with("gap:"));
+        assert!(issues[1].starts_with("overlap:"));
+    }
+
+    #[test]
+    fn test_journal_overlaps() {
+        let interval = |begin: &str, cease: &str, text: &str| Interval {
+            begin: at(begin),
+            cease: at(cease),
+            text: text.to_string(),
+        };
+        let entry = |begin: &str, cease: &str, text: &str| Entry {
+            begin: at(begin),
+            cease: at(cease),
+            text: text.to_string(),
+        };
+        // Newest first as from pick
+        let journal = vec![
+            entry("2024-04-01T13:00:00Z", "2024-04-01T17:00:00Z", "afternoon"),
+            entry("2024-04-01T10:00:00Z", "2024-04-01T12:00:00Z", "late"),
+            entry("2024-04-01T08:00:00Z", "2024-04-01T10:00:00Z", "early"),
+        ];
+        let intervals = vec![
+            interval("2024-04-01T09:30:00Z", "2024-04-01T10:30:00Z", "both"),
+            interval("2024-04-01T12:00:00Z", "2024-04-01T13:00:00Z", "lunch"),
+            interval("2024-04-01T18:00:00Z", "2024-04-01T19:00:00Z", "evening"),
+        ];
+        let overlaps = journal_overlaps(&intervals, &journal);
+        assert_eq!(2, overlaps.len());
+        assert!(overlaps[0].ends_with("overlaps journal 2024-04-01T08:00:00+00:00 \"early\""));
+        assert!(overlaps[1].ends_with("overlaps journal 2024-04-01T10:00:00+00:00 \"late\""));
+    }
+}
@@ -0,0 +1,244 @@
+use chrono::{Duration, NaiveDate};
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashMap};
+
+use crate::entry::Entry;
+use crate::period::Calendar;
+use crate::report::UNTAGGED;
+
+/// The `[interruptions]` section of the configuration.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default)]
+pub struct InterruptionConfig {
+    /// Entries shorter than this many minutes are interruptions.
+    pub minutes: i64,
+    /// Entries with any of these tags are interruptions, ie "@help".
+    pub tags: Vec<String>,
+    /// Entries with any of these tags or this text are breaks that end a
+    /// focus block, ie "+break" or "lunch".
+    pub breaks: Vec<String>,
+}
+
+impl Default for InterruptionConfig {
+    fn default() -> Self {
+        Self {
+            minutes: 5,
+            tags: Vec::new(),
+            breaks: Vec::new(),
+        }
+    }
+}
+
+impl InterruptionConfig {
+    pub fn is_interruption(&self, entry: &Entry) -> bool {
+        entry.duration() < Duration::minutes(self.minutes)
+            || entry
+                .tags()
+                .iter()
+                .any(|tag| self.tags.iter().any(|configured| configured == tag))
+    }
+
+    /// Whether the entry is a break, matching its text without regard to case.
+    pub fn is_break(&self, entry: &Entry) -> bool {
+        let tags = entry.tags();
+        self.breaks.iter().any(|configured| {
+            tags.contains(&configured.as_str())
+                || entry.text.trim().eq_ignore_ascii_case(configured)
+        })
+    }
+}
+
+/// Interruptions on one workday.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Day {
+    pub date: NaiveDate,
+    pub count: usize,
+    /// Time spent in the interruptions.
+    pub lost: Duration,
+    /// The most time in a row without an interruption or a break.
+    pub longest_focus: Duration,
+}
+
+/// Who or what interrupts, by tag.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Source {
+    pub name: String,
+    pub count: usize,
+    pub lost: Duration,
+}
+
+/// Find the interruptions and focus blocks of each workday.
+///
+/// # Arguments
+/// * `entries` - In any order, ie from pick.
+///
+/// # Returns
+/// Days oldest first and sources most frequent first. An interruption with
+/// two tags counts for both sources; one without tags is "(untagged)".
+/// Breaks, logged or not, are neither focus nor interruptions.
+pub fn analyze(
+    entries: &[Entry],
+    config: &InterruptionConfig,
+    calendar: &Calendar,
+) -> (Vec<Day>, Vec<Source>) {
+    let mut oldest_first: Vec<&Entry> = entries.iter().collect();
+    oldest_first.sort_by_key(|entry| entry.begin);
+
+    let mut days: BTreeMap<NaiveDate, Day> = BTreeMap::new();
+    let mut sources: HashMap<String, Source> = HashMap::new();
+    let mut focus = Duration::zero();
+    let mut previous: Option<&Entry> = None;
+    for entry in oldest_first {
+        let date = calendar.workday(entry.begin);
+        let day = days.entry(date).or_insert(Day {
+            date,
+            count: 0,
+            lost: Duration::zero(),
+            longest_focus: Duration::zero(),
+        });
+        // A break or a new workday ends the block
+        let follows = previous.is_some_and(|previous| {
+            previous.cease == entry.begin && calendar.workday(previous.begin) == date
+        });
+        if !follows {
+            focus = Duration::zero();
+        }
+        previous = Some(entry);
+
+        if config.is_break(entry) {
+            focus = Duration::zero();
+            continue;
+        }
+        if !config.is_interruption(entry) {
+            focus += entry.duration();
+            day.longest_focus = day.longest_focus.max(focus);
+            continue;
+        }
+        focus = Duration::zero();
+        day.count += 1;
+        day.lost += entry.duration();
+        let tags = entry.tags();
+        let names = match tags.is_empty() {
+            true => vec![UNTAGGED],
+            false => tags,
+        };
+        for name in names {
+            let source = sources.entry(name.to_string()).or_insert(Source {
+                name: name.to_string(),
+                count: 0,
+                lost: Duration::zero(),
+            });
+            source.count += 1;
+            source.lost += entry.duration();
+        }
+    }
+
+    let mut sources: Vec<Source> = sources.into_values().collect();
+    sources.sort_by(|a, b| {
+        b.count
+            .cmp(&a.count)
+            .then(b.lost.cmp(&a.lost))
+            .then(a.name.cmp(&b.name))
+    });
+    (days.into_values().collect(), sources)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::date_filter::ymd;
+    use chrono::DateTime;
+
+    fn entry(begin: &str, minutes: i64, text: &str) -> Entry {
+        let begin = DateTime::parse_from_rfc3339(begin).unwrap();
+        Entry {
+            begin,
+            cease: begin + Duration::minutes(minutes),
+            text: text.to_string(),
+        }
+    }
+
+    fn config() -> InterruptionConfig {
+        InterruptionConfig {
+            tags: vec!["@help".to_string()],
+            breaks: vec!["+break".to_string(), "lunch".to_string()],
+            ..InterruptionConfig::default()
+        }
+    }
+
+    #[test]
+    fn test_is_interruption() {
+        let config = config();
+        assert!(config.is_interruption(&entry("2024-04-01T09:00:00Z", 4, "email")));
+        assert!(config.is_interruption(&entry("2024-04-01T09:00:00Z", 30, "@help @CJ")));
+        assert!(!config.is_interruption(&entry("2024-04-01T09:00:00Z", 5, "email")));
+    }
+
+    #[test]
+    fn test_is_break() {
+        let config = config();
+        assert!(config.is_break(&entry("2024-04-01T12:00:00Z", 60, "Lunch")));
+        assert!(config.is_break(&entry("2024-04-01T15:00:00Z", 10, "+break coffee")));
+        assert!(!config.is_break(&entry("2024-04-01T12:00:00Z", 60, "lunch with +acme")));
+    }
+
+    #[test]
+    fn test_analyze_logged_break() {
+        let entries = vec![
+            entry("2024-04-01T13:00:00Z", 60, "+acme build"),
+            entry("2024-04-01T12:00:00Z", 60, "lunch"),
+            entry("2024-04-01T09:00:00Z", 180, "+acme build"),
+        ];
+        let (days, sources) = analyze(&entries, &config(), &Calendar::default());
+        assert_eq!(days[0].count, 0);
+        assert_eq!(days[0].longest_focus, Duration::minutes(180));
+        assert!(sources.is_empty());
+    }
+
+    #[test]
+    fn test_analyze() {
+        let entries = vec![
+            entry("2024-04-02T09:00:00Z", 240, "+acme build"),
+            entry("2024-04-01T13:00:00Z", 120, "+acme build"),
+            entry("2024-04-01T11:00:00Z", 60, "+acme build"),
+            entry("2024-04-01T10:30:00Z", 30, "@help @CJ printer"),
+            entry("2024-04-01T09:32:00Z", 58, "+acme build"),
+            entry("2024-04-01T09:30:00Z", 2, "@CJ question"),
+            entry("2024-04-01T09:00:00Z", 30, "+acme plan"),
+        ];
+        let (days, sources) = analyze(&entries, &config(), &Calendar::default());
+        assert_eq!(
+            days,
+            vec![
+                Day {
+                    date: ymd(2024, 4, 1),
+                    count: 2,
+                    lost: Duration::minutes(32),
+                    // After lunch; the hour before it does not join across the break
+                    longest_focus: Duration::minutes(120),
+                },
+                Day {
+                    date: ymd(2024, 4, 2),
+                    count: 0,
+                    lost: Duration::zero(),
+                    longest_focus: Duration::minutes(240),
+                }
+            ]
+        );
+        let names: Vec<(&str, usize)> = sources
+            .iter()
+            .map(|source| (source.name.as_str(), source.count))
+            .collect();
+        assert_eq!(names, vec![("@CJ", 2), ("@help", 1)]);
+    }
+
+    #[test]
+    fn test_config() {
+        let config =
+            crate::Config::from_toml("[interruptions]\ntags = [\"@help\"]\nbreaks = [\"lunch\"]")
+                .unwrap();
+        assert_eq!(config.interruptions.minutes, 5);
+        assert_eq!(config.interruptions.tags, vec!["@help"]);
+        assert_eq!(config.interruptions.breaks, vec!["lunch"]);
+    }
+}
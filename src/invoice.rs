@@ -0,0 +1,480 @@
+use crate::entry::Entry;
+use crate::rounding::{RoundMode, Rounding};
+use chrono::{Duration, NaiveDate};
+use serde::Deserialize;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// The `[invoice]` section of the configuration.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct InvoiceConfig {
+    /// Symbol before each amount, ie "$".
+    pub currency: String,
+    /// Fraction of the subtotal, ie 0.08 for 8%.
+    pub tax_rate: f64,
+    /// Round each entry to this many minutes; 0 leaves it as is.
+    pub round_minutes: i64,
+    pub round_mode: RoundMode,
+    /// Text before the invoice number, ie "INV-".
+    pub number_prefix: String,
+    pub rates: Vec<Rate>,
+}
+
+/// Hourly rate for a tag starting on an optional date.
+#[derive(Clone, Debug, Deserialize)]
+pub struct Rate {
+    pub tag: String,
+    pub hourly: f64,
+    #[serde(default, deserialize_with = "crate::config::optional_date")]
+    pub from: Option<NaiveDate>,
+}
+
+impl InvoiceConfig {
+    /// Rate in cents for the tag effective on the date.
+    ///
+    /// When several rates apply, the one with the latest `from` wins.
+    pub fn rate_cents(&self, tag: &str, date: NaiveDate) -> Option<i64> {
+        self.rates
+            .iter()
+            .filter(|rate| rate.tag == tag && rate.from.into_iter().all(|from| from <= date))
+            .max_by_key(|rate| rate.from)
+            .map(|rate| (rate.hourly * 100.0).round() as i64)
+    }
+
+    fn rounding(&self) -> Rounding {
+        Rounding::new(Duration::minutes(self.round_minutes), self.round_mode)
+    }
+}
+
+/// A billed entry.
+#[derive(Debug)]
+pub struct LineItem {
+    pub date: NaiveDate,
+    pub text: String,
+    /// Time after rounding.
+    pub time: Duration,
+    pub rate_cents: i64,
+    pub amount_cents: i64,
+}
+
+/// Line items billed at the rate of one tag.
+#[derive(Debug)]
+pub struct Section {
+    pub tag: String,
+    pub items: Vec<LineItem>,
+}
+
+impl Section {
+    pub fn time(&self) -> Duration {
+        self.items
+            .iter()
+            .fold(Duration::zero(), |sum, item| sum + item.time)
+    }
+
+    pub fn subtotal_cents(&self) -> i64 {
+        self.items.iter().map(|item| item.amount_cents).sum()
+    }
+}
+
+/// An invoice for one client.
+#[derive(Debug)]
+pub struct Invoice {
+    pub number: String,
+    pub client: String,
+    pub issued: NaiveDate,
+    pub currency: String,
+    pub tax_rate: f64,
+    pub sections: Vec<Section>,
+}
+
+impl Invoice {
+    /// Build an invoice from the entries tagged with the client.
+    ///
+    /// Each entry is billed at the rate of its first other tag with a
+    /// configured rate, otherwise at the client rate.
+    ///
+    /// # Arguments
+    /// * `entries` - Entries in any order, usually from `pick`.
+    /// * `client` - The client tag, ie "+acme".
+    /// * `config` - Rates, rounding, and tax.
+    /// * `number` - The invoice number.
+    /// * `issued` - The invoice date.
+    pub fn build(
+        entries: &[Entry],
+        client: &str,
+        config: &InvoiceConfig,
+        number: &str,
+        issued: NaiveDate,
+    ) -> Result<Self, String> {
+        let mut billed: Vec<&Entry> = entries
+            .iter()
+            .filter(|entry| entry.tags().contains(&client))
+            .collect();
+        if billed.is_empty() {
+            return Err(format!("no entries for {}", client));
+        }
+        billed.sort_by_key(|entry| entry.begin);
+
+        let rounding = config.rounding();
+        let mut sections: Vec<Section> = Vec::new();
+        for entry in billed {
+            let date = entry.begin.date_naive();
+            let (tag, rate_cents) = entry
+                .tags()
+                .into_iter()
+                .filter(|tag| *tag != client)
+                .chain(std::iter::once(client))
+                .find_map(|tag| config.rate_cents(tag, date).map(|rate| (tag, rate)))
+                .ok_or_else(|| format!("no rate for {} on {}", client, date))?;
+
+            let time = rounding.round(&entry.duration());
+            let item = LineItem {
+                date,
+                text: entry.text.clone(),
+                time,
+                rate_cents,
+                // Round half up to the nearest cent
+                amount_cents: (time.num_seconds() * rate_cents + 1800) / 3600,
+            };
+            match sections.iter_mut().find(|section| section.tag == tag) {
+                Some(section) => section.items.push(item),
+                None => sections.push(Section {
+                    tag: tag.to_string(),
+                    items: vec![item],
+                }),
+            }
+        }
+
+        Ok(Self {
+            number: number.to_string(),
+            client: client.to_string(),
+            issued,
+            currency: config.currency.clone(),
+            tax_rate: config.tax_rate,
+            sections,
+        })
+    }
+
+    pub fn subtotal_cents(&self) -> i64 {
+        self.sections.iter().map(Section::subtotal_cents).sum()
+    }
+
+    pub fn tax_cents(&self) -> i64 {
+        (self.subtotal_cents() as f64 * self.tax_rate).round() as i64
+    }
+
+    pub fn total_cents(&self) -> i64 {
+        self.subtotal_cents() + self.tax_cents()
+    }
+
+    /// First and last date billed.
+    pub fn period(&self) -> (NaiveDate, NaiveDate) {
+        let dates = self
+            .sections
+            .iter()
+            .flat_map(|section| section.items.iter().map(|item| item.date));
+        let first = dates.clone().min().unwrap_or(self.issued);
+        let last = dates.max().unwrap_or(self.issued);
+        (first, last)
+    }
+
+    fn money(&self, cents: i64) -> String {
+        format!("{}{}.{:02}", self.currency, cents / 100, cents % 100)
+    }
+
+    fn tax_label(&self) -> String {
+        format!("Tax ({}%)", (self.tax_rate * 10000.0).round() / 100.0)
+    }
+
+    pub fn to_markdown(&self) -> String {
+        let (first, last) = self.period();
+        let mut text = format!(
+            "# Invoice {}\n\nClient: {}  \nDate: {}  \nPeriod: {} to {}\n",
+            self.number, self.client, self.issued, first, last
+        );
+        for section in &self.sections {
+            text.push_str(&format!(
+                "\n## {}\n\n| Date | Description | Hours | Rate | Amount |\n|---|---|--:|--:|--:|\n",
+                section.tag
+            ));
+            for item in &section.items {
+                text.push_str(&format!(
+                    "| {} | {} | {} | {} | {} |\n",
+                    item.date,
+                    item.text.replace('|', "\\|"),
+                    hours(&item.time),
+                    self.money(item.rate_cents),
+                    self.money(item.amount_cents)
+                ));
+            }
+            text.push_str(&format!(
+                "| | **Subtotal** | {} | | {} |\n",
+                hours(&section.time()),
+                self.money(section.subtotal_cents())
+            ));
+        }
+        text.push_str(&format!(
+            "\n| | |\n|---|--:|\n| Subtotal | {} |\n| {} | {} |\n| **Total** | **{}** |\n",
+            self.money(self.subtotal_cents()),
+            self.tax_label(),
+            self.money(self.tax_cents()),
+            self.money(self.total_cents())
+        ));
+        text
+    }
+
+    pub fn to_html(&self) -> String {
+        let (first, last) = self.period();
+        let mut html = format!(
+            concat!(
+                "<!DOCTYPE html>\n<html>\n<head><meta charset=\"utf-8\"><title>Invoice {0}</title></head>\n",
+                "<body>\n<h1>Invoice {0}</h1>\n",
+                "<p>Client: {1}<br>Date: {2}<br>Period: {3} to {4}</p>\n"
+            ),
+            escape_html(&self.number),
+            escape_html(&self.client),
+            self.issued,
+            first,
+            last
+        );
+        for section in &self.sections {
+            html.push_str(&format!(
+                "<h2>{}</h2>\n<table>\n<tr><th>Date</th><th>Description</th><th>Hours</th><th>Rate</th><th>Amount</th></tr>\n",
+                escape_html(&section.tag)
+            ));
+            for item in &section.items {
+                html.push_str(&format!(
+                    "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>\n",
+                    item.date,
+                    escape_html(&item.text),
+                    hours(&item.time),
+                    self.money(item.rate_cents),
+                    self.money(item.amount_cents)
+                ));
+            }
+            html.push_str(&format!(
+                "<tr><td></td><th>Subtotal</th><td>{}</td><td></td><td>{}</td></tr>\n</table>\n",
+                hours(&section.time()),
+                self.money(section.subtotal_cents())
+            ));
+        }
+        html.push_str(&format!(
+            concat!(
+                "<table>\n<tr><th>Subtotal</th><td>{}</td></tr>\n",
+                "<tr><th>{}</th><td>{}</td></tr>\n",
+                "<tr><th>Total</th><td><strong>{}</strong></td></tr>\n</table>\n</body>\n</html>\n"
+            ),
+            self.money(self.subtotal_cents()),
+            self.tax_label(),
+            self.money(self.tax_cents()),
+            self.money(self.total_cents())
+        ));
+        html
+    }
+
+    pub fn to_csv(&self) -> Result<String, String> {
+        let mut writer = csv::Writer::from_writer(Vec::new());
+        let mut row = |fields: &[String]| writer.write_record(fields).map_err(|e| e.to_string());
+        row(&[
+            "invoice",
+            "date",
+            "tag",
+            "description",
+            "hours",
+            "rate",
+            "amount",
+        ]
+        .map(String::from))?;
+        for section in &self.sections {
+            for item in &section.items {
+                row(&[
+                    self.number.clone(),
+                    item.date.to_string(),
+                    section.tag.clone(),
+                    item.text.clone(),
+                    hours(&item.time),
+                    self.money(item.rate_cents),
+                    self.money(item.amount_cents),
+                ])?;
+            }
+            row(&[
+                self.number.clone(),
+                String::new(),
+                section.tag.clone(),
+                "Subtotal".to_string(),
+                hours(&section.time()),
+                String::new(),
+                self.money(section.subtotal_cents()),
+            ])?;
+        }
+        for (label, cents) in [
+            ("Subtotal".to_string(), self.subtotal_cents()),
+            (self.tax_label(), self.tax_cents()),
+            ("Total".to_string(), self.total_cents()),
+        ] {
+            let mut fields = vec![String::new(); 7];
+            fields[0] = self.number.clone();
+            fields[3] = label;
+            fields[6] = self.money(cents);
+            row(&fields)?;
+        }
+        let bytes = writer.into_inner().map_err(|e| e.to_string())?;
+        String::from_utf8(bytes).map_err(|e| e.to_string())
+    }
+}
+
+/// Decimal hours, ie "1.25".
+fn hours(time: &Duration) -> String {
+    format!("{:.2}", time.num_seconds() as f64 / 3600.0)
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Take the next number from the invoice sequence file.
+///
+/// The file holds the last number used and is created when missing.
+pub fn next_number(path: &Path) -> Result<u32, String> {
+    let last = match fs::read_to_string(path) {
+        Ok(content) => content
+            .trim()
+            .parse::<u32>()
+            .map_err(|e| format!("{}: {}", path.display(), e))?,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => 0,
+        Err(e) => return Err(format!("{}: {}", path.display(), e)),
+    };
+    let next = last + 1;
+    fs::write(path, format!("{}\n", next)).map_err(|e| format!("{}: {}", path.display(), e))?;
+    Ok(next)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::date_filter::ymd;
+    use chrono::DateTime;
+
+    fn entry(begin: &str, cease: &str, text: &str) -> Entry {
+        Entry {
+            begin: DateTime::parse_from_rfc3339(begin).unwrap(),
+            cease: DateTime::parse_from_rfc3339(cease).unwrap(),
+            text: text.to_string(),
+        }
+    }
+
+    fn config() -> InvoiceConfig {
+        Config::from_toml(concat!(
+            "[invoice]\n",
+            "currency = \"$\"\n",
+            "tax_rate = 0.1\n",
+            "round_minutes = 15\n",
+            "[[invoice.rates]]\n",
+            "tag = \"+acme\"\n",
+            "hourly = 100.0\n",
+            "[[invoice.rates]]\n",
+            "tag = \"+acme\"\n",
+            "hourly = 120.0\n",
+            "from = \"2024-04-01\"\n",
+            "[[invoice.rates]]\n",
+            "tag = \"@support\"\n",
+            "hourly = 60.0\n",
+        ))
+        .unwrap()
+        .invoice
+    }
+
+    #[test]
+    fn test_rate_effective_date() {
+        let config = config();
+        assert_eq!(config.rate_cents("+acme", ymd(2024, 3, 31)), Some(10000));
+        assert_eq!(config.rate_cents("+acme", ymd(2024, 4, 1)), Some(12000));
+        assert_eq!(config.rate_cents("+other", ymd(2024, 4, 1)), None);
+    }
+
+    #[test]
+    fn test_build() {
+        let entries = vec![
+            entry(
+                "2024-04-01T09:00:00Z",
+                "2024-04-01T09:50:00Z",
+                "+acme review",
+            ),
+            entry(
+                "2024-04-01T08:00:00Z",
+                "2024-04-01T08:10:00Z",
+                "+acme @support call",
+            ),
+            entry(
+                "2024-03-29T08:00:00Z",
+                "2024-03-29T09:00:00Z",
+                "+acme planning",
+            ),
+            entry(
+                "2024-04-01T10:00:00Z",
+                "2024-04-01T11:00:00Z",
+                "+other work",
+            ),
+        ];
+        let invoice = Invoice::build(&entries, "+acme", &config(), "7", ymd(2024, 4, 30)).unwrap();
+
+        assert_eq!(invoice.sections.len(), 2);
+        assert_eq!(invoice.sections[0].tag, "+acme");
+        // 60 minutes at $100 and 60 minutes (rounded up from 50) at $120
+        assert_eq!(invoice.sections[0].subtotal_cents(), 22000);
+        assert_eq!(invoice.sections[1].tag, "@support");
+        assert_eq!(invoice.sections[1].subtotal_cents(), 1500);
+        assert_eq!(invoice.subtotal_cents(), 23500);
+        assert_eq!(invoice.tax_cents(), 2350);
+        assert_eq!(invoice.total_cents(), 25850);
+        assert_eq!(invoice.period(), (ymd(2024, 3, 29), ymd(2024, 4, 1)));
+    }
+
+    #[test]
+    fn test_build_without_rate() {
+        let entries = vec![entry(
+            "2024-04-01T09:00:00Z",
+            "2024-04-01T09:50:00Z",
+            "+globex review",
+        )];
+        let error = Invoice::build(&entries, "+globex", &config(), "7", ymd(2024, 4, 30));
+        assert_eq!(error.unwrap_err(), "no rate for +globex on 2024-04-01");
+    }
+
+    #[test]
+    fn test_to_csv() {
+        let entries = vec![entry(
+            "2024-04-01T09:00:00Z",
+            "2024-04-01T09:30:00Z",
+            "+acme review, notes",
+        )];
+        let invoice = Invoice::build(&entries, "+acme", &config(), "7", ymd(2024, 4, 30)).unwrap();
+        assert_eq!(
+            invoice.to_csv().unwrap(),
+            concat!(
+                "invoice,date,tag,description,hours,rate,amount\n",
+                "7,2024-04-01,+acme,\"+acme review, notes\",0.50,$120.00,$60.00\n",
+                "7,,+acme,Subtotal,0.50,,$60.00\n",
+                "7,,,Subtotal,,,$60.00\n",
+                "7,,,Tax (10%),,,$6.00\n",
+                "7,,,Total,,,$66.00\n",
+            )
+        );
+    }
+
+    #[test]
+    fn test_next_number() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("invoice-number");
+        assert_eq!(next_number(&path), Ok(1));
+        assert_eq!(next_number(&path), Ok(2));
+        // Unreadable is not the same as missing
+        assert!(next_number(dir.path()).is_err());
+    }
+}
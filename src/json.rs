@@ -0,0 +1,249 @@
+use crate::entry::{hh_mm, Entry};
+use crate::stats::Stats;
+use chrono::Duration;
+use serde::Serialize;
+
+/// Version of the JSON layout.
+///
+/// Increase it when a field is removed or changes meaning.
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// An entry as JSON.
+#[derive(Debug, Serialize)]
+pub struct Record<'a> {
+    pub begin: String,
+    pub cease: String,
+    pub seconds: i64,
+    pub duration: String,
+    pub text: &'a str,
+    pub tags: Vec<&'a str>,
+}
+
+impl<'a> From<&'a Entry> for Record<'a> {
+    fn from(entry: &'a Entry) -> Self {
+        Self {
+            begin: entry.begin.to_rfc3339(),
+            cease: entry.cease.to_rfc3339(),
+            seconds: entry.duration().num_seconds(),
+            duration: entry.hh_mm(),
+            text: &entry.text,
+            tags: entry.tags(),
+        }
+    }
+}
+
+impl Record<'_> {
+    /// Show a duration other than the entry's own, ie rounded.
+    pub fn with_duration(mut self, duration: &Duration) -> Self {
+        self.seconds = duration.num_seconds();
+        self.duration = hh_mm(duration);
+        self
+    }
+}
+
+/// Sum of the entry durations.
+#[derive(Debug, Serialize)]
+pub struct Total {
+    pub seconds: i64,
+    pub duration: String,
+}
+
+impl From<&Duration> for Total {
+    fn from(duration: &Duration) -> Self {
+        Self {
+            seconds: duration.num_seconds(),
+            duration: hh_mm(duration),
+        }
+    }
+}
+
+/// The single document written by `--json`.
+#[derive(Debug, Serialize)]
+pub struct Envelope<'a> {
+    pub version: u32,
+    pub entries: Vec<Record<'a>>,
+    pub total: Total,
+}
+
+impl<'a> Envelope<'a> {
+    pub fn new(entries: &'a [Entry]) -> Self {
+        let durations: Vec<Duration> = entries.iter().map(|entry| entry.duration()).collect();
+        Self::with_durations(entries, &durations)
+    }
+
+    /// Entries shown with the given durations, which are also totaled.
+    pub fn with_durations(entries: &'a [Entry], durations: &[Duration]) -> Self {
+        let total = durations
+            .iter()
+            .fold(Duration::zero(), |sum, duration| sum + *duration);
+        Self {
+            version: SCHEMA_VERSION,
+            entries: entries
+                .iter()
+                .zip(durations)
+                .map(|(entry, duration)| Record::from(entry).with_duration(duration))
+                .collect(),
+            total: Total::from(&total),
+        }
+    }
+}
+
+/// The document written by `stats --json`.
+#[derive(Debug, Serialize)]
+pub struct Summary<'a> {
+    pub version: u32,
+    pub days: usize,
+    pub total: Total,
+    pub average_day: Total,
+    pub median_day: Total,
+    /// "HH:MM" or null without entries
+    pub typical_start: Option<String>,
+    pub typical_stop: Option<String>,
+    pub longest: Vec<Record<'a>>,
+    pub short_entries: usize,
+    pub short_per_day: f64,
+    pub tags: Vec<TagTotal<'a>>,
+}
+
+/// Time spent on one tag in `stats --json`.
+#[derive(Debug, Serialize)]
+pub struct TagTotal<'a> {
+    pub tag: &'a str,
+    pub seconds: i64,
+    pub duration: String,
+    pub percent: f64,
+}
+
+impl<'a> From<&'a Stats> for Summary<'a> {
+    fn from(stats: &'a Stats) -> Self {
+        let time = |time: &Option<chrono::NaiveTime>| time.map(|t| t.format("%H:%M").to_string());
+        Self {
+            version: SCHEMA_VERSION,
+            days: stats.days,
+            total: Total::from(&stats.total),
+            average_day: Total::from(&stats.average_day),
+            median_day: Total::from(&stats.median_day),
+            typical_start: time(&stats.typical_start),
+            typical_stop: time(&stats.typical_stop),
+            longest: stats.longest.iter().map(Record::from).collect(),
+            short_entries: stats.short_entries,
+            short_per_day: stats.short_per_day(),
+            tags: stats
+                .tags
+                .iter()
+                .map(|share| TagTotal {
+                    tag: &share.tag,
+                    seconds: share.duration.num_seconds(),
+                    duration: hh_mm(&share.duration),
+                    percent: share.percent,
+                })
+                .collect(),
+        }
+    }
+}
+
+/// One line written by `--json-lines`.
+///
+/// Each line is either `{"version":1,"entry":{...}}` or
+/// `{"version":1,"total":{...}}`.
+#[derive(Debug, Serialize)]
+pub struct Line<'a> {
+    pub version: u32,
+    #[serde(flatten)]
+    pub item: LineItem<'a>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LineItem<'a> {
+    Entry(Record<'a>),
+    Total(Total),
+}
+
+impl<'a> Line<'a> {
+    pub fn entry(entry: &'a Entry) -> Self {
+        Self::record(Record::from(entry))
+    }
+
+    pub fn record(record: Record<'a>) -> Self {
+        Self {
+            version: SCHEMA_VERSION,
+            item: LineItem::Entry(record),
+        }
+    }
+
+    pub fn total(duration: &Duration) -> Self {
+        Self {
+            version: SCHEMA_VERSION,
+            item: LineItem::Total(Total::from(duration)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::DateTime;
+
+    fn entry() -> Entry {
+        Entry {
+            begin: DateTime::parse_from_rfc3339("2024-04-01T08:00:00Z").unwrap(),
+            cease: DateTime::parse_from_rfc3339("2024-04-01T08:10:00Z").unwrap(),
+            text: "+acme \"quoted\" call with @CJ".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_line_entry() {
+        let entry = entry();
+        let actual = serde_json::to_string(&Line::entry(&entry)).unwrap();
+        assert_eq!(
+            actual,
+            concat!(
+                r#"{"version":1,"entry":{"begin":"2024-04-01T08:00:00+00:00","#,
+                r#""cease":"2024-04-01T08:10:00+00:00","seconds":600,"duration":"00:10","#,
+                r#""text":"+acme \"quoted\" call with @CJ","tags":["+acme","@CJ"]}}"#
+            )
+        );
+    }
+
+    #[test]
+    fn test_line_total() {
+        let actual = serde_json::to_string(&Line::total(&Duration::minutes(90))).unwrap();
+        assert_eq!(
+            actual,
+            r#"{"version":1,"total":{"seconds":5400,"duration":"01:30"}}"#
+        );
+    }
+
+    #[test]
+    fn test_envelope() {
+        let entries = vec![entry(), entry()];
+        let envelope = Envelope::new(&entries);
+        assert_eq!(envelope.version, SCHEMA_VERSION);
+        assert_eq!(envelope.entries.len(), 2);
+        assert_eq!(envelope.total.seconds, 1200);
+    }
+
+    #[test]
+    fn test_summary() {
+        let entries = vec![entry()];
+        let stats = crate::stats::stats(&entries, &Default::default(), Duration::minutes(5), 1);
+        let actual = serde_json::to_value(Summary::from(&stats)).unwrap();
+        assert_eq!(actual["days"], 1);
+        assert_eq!(actual["median_day"]["seconds"], 600);
+        assert_eq!(actual["typical_start"], "08:00");
+        assert_eq!(actual["tags"][0]["tag"], "+acme");
+        assert_eq!(actual["tags"][0]["percent"], 100.0);
+    }
+
+    #[test]
+    fn test_envelope_with_durations() {
+        let entries = vec![entry(), entry()];
+        let durations = vec![Duration::minutes(15), Duration::minutes(0)];
+        let envelope = Envelope::with_durations(&entries, &durations);
+        assert_eq!(envelope.entries[0].duration, "00:15");
+        assert_eq!(envelope.entries[1].seconds, 0);
+        assert_eq!(envelope.total.seconds, 900);
+    }
+}
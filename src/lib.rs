@@ -1,8 +1,28 @@
+pub mod balance;
+pub mod budget;
+pub mod chart;
+pub mod check;
+mod config;
+pub use config::{config_dir, get_config_path, Config};
+
 mod date_filter;
 pub use date_filter::DateFilter;
 
 mod entry;
 pub use entry::{hh_mm, pick, Entry, EntryIterator};
 
+pub mod export;
+pub mod heatmap;
+pub mod holidays;
+pub mod import;
+pub mod interruptions;
+pub mod invoice;
+pub mod json;
+pub mod period;
+pub mod report;
+pub mod rounding;
+pub mod stats;
+
 mod tsv;
-pub use tsv::{get_tsv_path, write_to_tsv};
+pub use tsv::{get_tsv_path, merge_into_tsv, write_to_tsv};
+pub mod window;
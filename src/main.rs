@@ -1,8 +1,10 @@
-use chrono::{DateTime, Duration, FixedOffset};
-use clap::{Args, Parser, Subcommand};
+use chrono::{DateTime, Datelike, Duration, FixedOffset, NaiveDate};
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use idid::period::Calendar;
 use rand::seq::SliceRandom;
 use std::env;
 use std::fs;
+use std::io;
 use std::path::PathBuf;
 
 mod date_parse;
@@ -14,7 +16,8 @@ use util_time::current_datetime;
 enum Commands {
     /// Start tracking time.
     Start {
-        /// WHEN minutes ago or time, ie "8am", "13:15", "4:55pm"
+        /// WHEN minutes or duration ago, or time, ie "10", "1h30m", "8am", "13:15", "4:55 pm", "noon"
+        /// or after the last entry, ie "last+20m"
         #[arg(short = 't', value_name = "WHEN")]
         offset: Option<String>,
 
@@ -26,10 +29,15 @@ enum Commands {
     /// Add something note worthy.
     #[command(arg_required_else_help = true)]
     Add {
-        /// WHEN minutes ago or time, ie "8am", "13:15", "4:55pm"
+        /// WHEN minutes or duration ago, or time, ie "10", "1h30m", "8am", "13:15", "4:55 pm", "noon"
+        /// or after the last entry, ie "last+20m"
         #[arg(short = 't', value_name = "WHEN")]
         offset: Option<String>,
 
+        /// Ended DURATION after the last entry, ie "45m" or "1h30m"
+        #[arg(long, value_name = "DURATION", conflicts_with = "offset")]
+        took: Option<String>,
+
         /// Quiet response
         #[arg(short, long)]
         quiet: bool,
@@ -48,28 +56,246 @@ enum Commands {
         lines: Option<u32>,
     },
 
+    /// Total for today so far and the time since the last entry.
+    Status {
+        /// Sparkline of the last 14 days
+        #[arg(long)]
+        spark: bool,
+
+        /// ASCII instead of Unicode blocks
+        #[arg(long, requires = "spark")]
+        no_unicode: bool,
+    },
+
+    /// Time used and left of each [[budget]] this period.
+    Budget,
+
+    /// Over- and under-time against the [balance] targets since its anchor.
+    Balance {
+        /// A row per day instead of per week
+        #[arg(long)]
+        days: bool,
+    },
+
+    /// Look for mistakes in the TSV, ie times out of order.
+    Check,
+
     /// Show DATEs or DATE range entries.
     Show {
         #[clap(flatten)]
         args: ArgsShow,
 
-        /// Show range total
-        #[arg(short = 't', long = "total", help = "Total duration.")]
-        total: bool,
+        #[clap(flatten)]
+        output: ArgsOutput,
+
+        #[clap(flatten)]
+        round: ArgsRound,
+    },
+
+    /// Total time in DATEs or DATE range by period or tag.
+    Report {
+        #[clap(flatten)]
+        args: ArgsShow,
+
+        /// Group entries by
+        #[arg(long, value_enum, default_value_t = ReportBy::Day)]
+        by: ReportBy,
 
-        /// Show duration in seconds
+        #[clap(flatten)]
+        round: ArgsRound,
+
+        /// Round each entry or each row's time per day
+        #[arg(long, value_enum, default_value_t = RoundPer::Entry, requires = "round")]
+        round_per: RoundPer,
+
+        /// Bars scaled to $COLUMNS instead of tab-separated rows
         #[arg(long)]
-        seconds: bool,
+        chart: bool,
+
+        /// ASCII instead of Unicode blocks
+        #[arg(long, requires = "chart")]
+        no_unicode: bool,
+
+        /// Interruptions per day, their sources, and the longest focus instead
+        #[arg(
+            long,
+            conflicts_with_all = [
+                "by", "round", "round_mode", "round_per", "carry", "chart", "no_unicode"
+            ]
+        )]
+        interruptions: bool,
+    },
+
+    /// Patterns in DATEs or DATE range, ie typical day length and start time.
+    Stats {
+        #[clap(flatten)]
+        args: ArgsShow,
 
-        /// Json output
+        /// Count entries shorter than DURATION as interruptions
+        #[arg(long, value_name = "DURATION", default_value = "5m", value_parser = time_parse::duration_from_str)]
+        short: Duration,
+
+        /// How many of the longest entries to list
+        #[arg(long, value_name = "N", default_value_t = 5)]
+        top: usize,
+
+        /// JSON document instead of text
         #[arg(long)]
         json: bool,
     },
+
+    /// Calendar of tracked hours per day over a year.
+    Heatmap {
+        /// Year to show; this year by default
+        #[arg(long)]
+        year: Option<i32>,
+
+        /// Write an SVG image to FILE instead
+        #[arg(long, value_name = "FILE")]
+        svg: Option<PathBuf>,
+
+        /// ASCII instead of Unicode shading
+        #[arg(long, conflicts_with = "svg")]
+        no_unicode: bool,
+    },
+
+    /// Export DATEs or DATE range entries for other tools.
+    Export {
+        #[clap(flatten)]
+        args: ArgsShow,
+
+        /// Org-mode outline with CLOCK lines
+        #[arg(long, required = true)]
+        org: bool,
+    },
+
+    /// Create an invoice from a client's entries in DATEs or DATE range.
+    #[command(arg_required_else_help = true)]
+    Invoice {
+        #[clap(flatten)]
+        args: ArgsShow,
+
+        /// Client tag, ie "+acme"
+        #[arg(long, required = true)]
+        client: String,
+
+        /// Invoice format
+        #[arg(long, value_enum, default_value_t = InvoiceFormat::Markdown)]
+        format: InvoiceFormat,
+
+        /// Do not take the next invoice number
+        #[arg(long)]
+        draft: bool,
+    },
+
+    /// Import entries exported from another time tracker.
+    #[command(arg_required_else_help = true)]
+    Import {
+        /// Time tracker that created FILE
+        #[arg(long, value_enum)]
+        from: ImportFrom,
+
+        /// Exported file to import
+        #[arg(value_name = "FILE")]
+        file: PathBuf,
+
+        /// Show the entries as `show` would without changing the TSV
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Import even when entries overlap the TSV, which changes their durations
+        #[arg(long)]
+        force: bool,
+    },
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum ReportBy {
+    Day,
+    Week,
+    Month,
+    Quarter,
+    Year,
+    /// Pay period from the [calendar] config
+    Pp,
+    /// Fiscal quarter from the [calendar] config
+    Fq,
+    Tag,
+}
+
+impl From<ReportBy> for idid::report::Group {
+    fn from(by: ReportBy) -> Self {
+        match by {
+            ReportBy::Day => Self::Day,
+            ReportBy::Week => Self::Week,
+            ReportBy::Month => Self::Month,
+            ReportBy::Quarter => Self::Quarter,
+            ReportBy::Year => Self::Year,
+            ReportBy::Pp => Self::PayPeriod,
+            ReportBy::Fq => Self::FiscalQuarter,
+            ReportBy::Tag => Self::Tag,
+        }
+    }
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum RoundMode {
+    Up,
+    Down,
+    Nearest,
+}
+
+impl From<RoundMode> for idid::rounding::RoundMode {
+    fn from(mode: RoundMode) -> Self {
+        match mode {
+            RoundMode::Up => Self::Up,
+            RoundMode::Down => Self::Down,
+            RoundMode::Nearest => Self::Nearest,
+        }
+    }
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum RoundPer {
+    Entry,
+    Day,
+}
+
+impl From<RoundPer> for idid::report::RoundPer {
+    fn from(per: RoundPer) -> Self {
+        match per {
+            RoundPer::Entry => Self::Entry,
+            RoundPer::Day => Self::Day,
+        }
+    }
+}
+
+/// How to draw `report --chart`
+#[derive(Clone, Copy, Debug)]
+struct Chart {
+    unicode: bool,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum InvoiceFormat {
+    Markdown,
+    Html,
+    Csv,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum ImportFrom {
+    /// Toggl Track CSV export
+    Toggl,
+    /// Clockify CSV export
+    Clockify,
+    /// Watson frames JSON
+    Watson,
 }
 
 #[derive(Args, Debug)]
 struct ArgsAdd {
-    /// WHEN minutes ago or time, ie "8am", "13:15", "4:55pm"
+    /// WHEN minutes or duration ago, or time, ie "10", "1h30m", "8am", "13:15", "4:55 pm", "noon"
     #[arg(short = 't', value_name = "WHEN")]
     offset: Option<String>,
 
@@ -82,6 +308,51 @@ struct ArgsAdd {
     text: Vec<String>,
 }
 
+#[derive(Args, Debug)]
+struct ArgsOutput {
+    /// Show range total
+    #[arg(short = 't', long = "total", help = "Total duration.")]
+    total: bool,
+
+    /// Show duration in seconds
+    #[arg(long)]
+    seconds: bool,
+
+    /// JSON document with all entries and the total
+    #[arg(long)]
+    json: bool,
+
+    /// JSON Lines output; one entry per line
+    #[arg(long, conflicts_with = "json")]
+    json_lines: bool,
+
+    /// Only the part of each entry within a daily window, ie "18:00-08:00"
+    #[arg(long, value_name = "HH:MM-HH:MM")]
+    between: Option<idid::window::TimeWindow>,
+}
+
+#[derive(Args, Debug)]
+struct ArgsRound {
+    /// Round durations to a billing increment, ie "6m" or "15m"
+    #[arg(long, value_name = "DURATION", value_parser = time_parse::duration_from_str)]
+    round: Option<Duration>,
+
+    /// Direction to round
+    #[arg(long, value_enum, default_value_t = RoundMode::Up, requires = "round")]
+    round_mode: RoundMode,
+
+    /// Add what rounding left over to the next duration
+    #[arg(long, requires = "round")]
+    carry: bool,
+}
+
+impl ArgsRound {
+    fn rounding(&self) -> Option<idid::rounding::Rounding> {
+        self.round
+            .map(|increment| idid::rounding::Rounding::new(increment, self.round_mode.into()))
+    }
+}
+
 #[derive(Args, Debug)]
 struct ArgsShow {
     /// DATE can be any of:
@@ -93,12 +364,48 @@ struct ArgsShow {
     ///  -  Last MMDD (leading 0, dashes optinal); within 364ish days.
     ///  -  Last week day with optional numeric suffix to add weeks.
     ///     "mon" is last Monday and "mon1" goes back an additional week.
+    ///  -  A whole period: "thisweek", "lastweek", "thismonth", "lastmonth",
+    ///     "thisquarter", "lastquarter", "thisyear", or "lastyear".
+    ///  -  ISO week YYYY-Www, month YYYY-MM, quarter YYYY-Qn, or year YYYY.
+    ///  -  Days, weeks, months, or years ending today: "7d", "2w", "3m", "1y".
+    ///  -  Range of two DATEs joined by "..", ie "0401..0415" or "mon.." to today.
+    ///  -  A quoted phrase: "3 days ago", "last friday", "friday before last",
+    ///     "beginning of month", "first monday of april", or "april 1".
     #[arg(value_name="DATE", num_args=0.., help="See --help for allowed formats.", verbatim_doc_comment)]
     dates: Option<Vec<String>>,
 
     /// Pick entries inclusive of range
     #[arg(short = 'r', long, value_name = "DATE", num_args = 2)]
     range: Option<Vec<String>>,
+
+    /// Pick entries on or after DATE
+    #[arg(long, value_name = "DATE")]
+    since: Option<String>,
+
+    /// Pick entries on or before DATE
+    #[arg(long, value_name = "DATE")]
+    until: Option<String>,
+
+    /// Leave out DATE; may be repeated
+    #[arg(long, value_name = "DATE")]
+    except: Option<Vec<String>>,
+
+    /// Split entries crossing the start of a day so each part counts on its own day
+    #[arg(long)]
+    split_days: bool,
+
+    /// Only Monday through Friday
+    #[arg(long, conflicts_with = "weekends")]
+    weekdays: bool,
+
+    /// Only Saturday and Sunday
+    #[arg(long)]
+    weekends: bool,
+
+    /// Skip dates in FILE, a list of YYYY-MM-DD or an .ics calendar.
+    /// Relative paths are also looked for in the config directory.
+    #[arg(long, value_name = "FILE")]
+    exclude_holidays: Option<PathBuf>,
 }
 
 #[derive(Parser)]
@@ -111,6 +418,14 @@ struct Cli {
     /// TSV file instead of $ididTSV or $XDG_DATA_HOME/idid/idid.tsv
     #[arg(long, value_name = "FILE")]
     tsv: Option<PathBuf>,
+
+    /// Config file instead of $ididCONFIG or $XDG_CONFIG_HOME/idid/config.toml
+    #[arg(long, value_name = "FILE")]
+    config: Option<PathBuf>,
+
+    /// Show and pick days in an IANA zone, ie "America/Chicago" or "UTC"
+    #[arg(long, value_name = "ZONE", value_parser = parse_zone)]
+    tz: Option<chrono_tz::Tz>,
 }
 
 fn main() {
@@ -119,14 +434,28 @@ fn main() {
         .unwrap()
         .to_string_lossy()
         .to_string();
+    let mut config = load_config(&cli.config);
+    if cli.tz.is_some() {
+        config.calendar.timezone = cli.tz;
+    }
 
     match &cli.command {
         Some(Commands::Add {
             offset,
+            took,
             quiet,
             text,
         }) => {
-            command_add(&tsv, offset.as_deref(), quiet, text);
+            // "--took 45m" is the same as "-t last+45m"
+            let offset = match took {
+                Some(took) => Some(format!("last+{}", took)),
+                None => offset.clone(),
+            };
+            // Adding works without budgets
+            if let Err(e) = config.section("budget") {
+                eprintln!("WARNING: {}", e);
+            }
+            command_add(&tsv, &config, offset.as_deref(), quiet, text);
         }
         Some(Commands::Start { offset, quiet }) => {
             command_start(&tsv, offset, quiet);
@@ -135,50 +464,110 @@ fn main() {
             command_edit(&tsv);
         }
         Some(Commands::Last { lines }) => {
-            command_last(&tsv, lines);
+            command_last(&tsv, &config, lines);
+        }
+        Some(Commands::Status { spark, no_unicode }) => {
+            if let Err(e) = config.section("balance") {
+                eprintln!("WARNING: {}", e);
+            }
+            command_status(&tsv, &config, *spark, !no_unicode);
+        }
+        Some(Commands::Budget) => {
+            require_section(&config, "budget");
+            command_budget(&tsv, &config);
+        }
+        Some(Commands::Balance { days }) => {
+            require_section(&config, "balance");
+            command_balance(&tsv, &config, *days);
+        }
+        Some(Commands::Check) => {
+            command_check(&tsv, &config);
         }
         Some(Commands::Show {
             args,
-            total,
-            seconds,
-            json,
+            output,
+            round,
         }) => {
-            let filter = date_filter_from_date_args(args);
-            if filter.is_empty() {
-                eprintln!("Error: at least one of --dates or --range is required");
-                std::process::exit(1);
-            }
-
-            let mut total_duration = Duration::zero();
-            for entry in idid::pick(tsv, &filter) {
-                total_duration += entry.duration();
-                println!("{}", entry.serialize(seconds, *json));
-            }
-            if *total && !*json && !*seconds && total_duration > Duration::zero() {
-                println!(
-                    "                Total    \t{}",
-                    idid::hh_mm(&total_duration)
-                );
+            command_show(&tsv, &config, args, output, round);
+        }
+        Some(Commands::Report {
+            args,
+            by,
+            round,
+            round_per,
+            chart,
+            no_unicode,
+            interruptions,
+        }) => {
+            if *interruptions {
+                require_section(&config, "interruptions");
+                command_interruptions(&tsv, &config, args);
+            } else {
+                let chart = chart.then_some(Chart {
+                    unicode: !no_unicode,
+                });
+                command_report(&tsv, &config, args, by, round, round_per, chart);
             }
         }
-        None => {
-            #[cfg(debug_assertions)]
-            println!("None: current tsv={}", tsv);
+        Some(Commands::Stats {
+            args,
+            short,
+            top,
+            json,
+        }) => {
+            command_stats(&tsv, &config, args, short, *top, *json);
+        }
+        Some(Commands::Heatmap {
+            year,
+            svg,
+            no_unicode,
+        }) => {
+            command_heatmap(&tsv, &config, *year, svg.as_deref(), !no_unicode);
+        }
+        Some(Commands::Export { args, org: _ }) => {
+            command_export(&tsv, &config, args);
         }
+        Some(Commands::Invoice {
+            args,
+            client,
+            format,
+            draft,
+        }) => {
+            require_section(&config, "invoice");
+            command_invoice(&tsv, &config, args, client, format, draft);
+        }
+        Some(Commands::Import {
+            from,
+            file,
+            dry_run,
+            force,
+        }) => {
+            command_import(&tsv, from, file, dry_run, force);
+        }
+        None => {}
     }
 }
 
 /// Add a line to the end of the TSV
-fn command_add(tsv: &str, offset: Option<&str>, quiet: &bool, text: &[String]) {
-    match offset_from_current_or_current(offset) {
+fn command_add(
+    tsv: &str,
+    config: &idid::Config,
+    offset: Option<&str>,
+    quiet: &bool,
+    text: &[String],
+) {
+    match offset_from_current_or_current(tsv, offset) {
         Ok(ended) => {
             if text.is_empty() {
                 eprintln!("Error: missing text");
                 std::process::exit(1);
             }
-            let timestamp = get_last_entry_timestamp(tsv).expect("Bad last entry");
+            let timestamp = get_last_entry_timestamp(tsv).unwrap_or_else(|e| {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            });
             idid::write_to_tsv(tsv, &ended, Some(&text.join(" ")));
-            let duration = current_datetime() - timestamp;
+            let duration = ended - timestamp;
             if duration > Duration::hours(12) {
                 println!(
                     "WARNING: elapsed time from last is {:>2}:{:>02}",
@@ -198,6 +587,14 @@ fn command_add(tsv: &str, offset: Option<&str>, quiet: &bool, text: &[String]) {
                 );
                 praise();
             }
+            let added = idid::Entry {
+                begin: timestamp,
+                cease: ended,
+                text: text.join(" "),
+            };
+            for warning in budget_warnings(tsv, config, &added) {
+                println!("WARNING: {}", warning);
+            }
         }
         Err(e) => {
             eprintln!("Error: {}", e);
@@ -206,6 +603,79 @@ fn command_add(tsv: &str, offset: Option<&str>, quiet: &bool, text: &[String]) {
     }
 }
 
+/// Time used of a budget in the period containing the date
+fn budget_usage(
+    tsv: &str,
+    config: &idid::Config,
+    budget: &idid::budget::Budget,
+    date: NaiveDate,
+) -> Result<idid::budget::Usage, String> {
+    let span = budget.span(date, &config.calendar)?;
+    let filter = with_calendar(
+        idid::DateFilter::new(&[span.0, span.1], &[]),
+        &config.calendar,
+    );
+    let entries: Vec<idid::Entry> = idid::pick(tsv, &filter).collect();
+    Ok(budget.usage(&entries, span, &config.calendar))
+}
+
+/// Budgets of the entry's tags that it pushed to 80% or past 100%
+fn budget_warnings(tsv: &str, config: &idid::Config, added: &idid::Entry) -> Vec<String> {
+    let tags = added.tags();
+    let day = config.calendar.workday(added.begin);
+    config
+        .budgets
+        .iter()
+        .filter(|budget| tags.contains(&budget.tag.as_str()))
+        .filter_map(|budget| {
+            let usage = match budget_usage(tsv, config, budget, day) {
+                Ok(usage) => usage,
+                Err(e) => return Some(e),
+            };
+            let threshold = usage.crossed(added.duration())?;
+            let status = match threshold >= 100 {
+                true => "over budget",
+                false => "nearly out of budget",
+            };
+            Some(format!(
+                "{} is {} with {} of {} used ({:.0}%) for {}..{}",
+                budget.tag,
+                status,
+                idid::hh_mm(&usage.used),
+                idid::hh_mm(&usage.budget),
+                usage.percent(),
+                usage.first,
+                usage.last
+            ))
+        })
+        .collect()
+}
+
+/// Write used, budget, and remaining time of each budget for today
+fn command_budget(tsv: &str, config: &idid::Config) {
+    if config.budgets.is_empty() {
+        eprintln!("Error: add a [[budget]] with a tag, hours, and period to the config");
+        std::process::exit(2);
+    }
+    let today = date_parse::today(&config.calendar);
+    for budget in &config.budgets {
+        let usage = budget_usage(tsv, config, budget, today).unwrap_or_else(|e| {
+            eprintln!("Error: {}", e);
+            std::process::exit(2);
+        });
+        println!(
+            "{}\t{}..{}\t{}\t{}\t{}\t{:.0}%",
+            budget.tag,
+            usage.first,
+            usage.last,
+            idid::hh_mm(&usage.used),
+            idid::hh_mm(&usage.budget),
+            idid::balance::signed(&usage.remaining()),
+            usage.percent()
+        );
+    }
+}
+
 fn command_edit(tsv: &str) {
     // Get the value of the EDITOR environment variable
     let editor = match env::var("EDITOR") {
@@ -231,8 +701,389 @@ fn command_edit(tsv: &str) {
     }
 }
 
+/// Today's total and the time since the last entry
+fn command_status(tsv: &str, config: &idid::Config, spark: bool, unicode: bool) {
+    let calendar = &config.calendar;
+    let today = date_parse::today(calendar);
+    let filter = with_calendar(idid::DateFilter::new(&[], &[today]), calendar);
+    let entries: Vec<idid::Entry> = idid::pick(tsv, &filter).collect();
+    let total = entries
+        .iter()
+        .fold(Duration::zero(), |sum, entry| sum + entry.duration());
+    println!(
+        "Today {}: {} in {} entries",
+        today,
+        idid::hh_mm(&total),
+        entries.len()
+    );
+
+    // A new TSV has no last entry
+    match get_last_entry_timestamp(tsv) {
+        Ok(timestamp) if calendar.workday(timestamp) == today => println!(
+            "Last entry {} ago",
+            idid::hh_mm(&(current_datetime() - timestamp))
+        ),
+        Ok(_) => {}
+        Err(e) if e == "No entries" => {}
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            std::process::exit(2);
+        }
+    }
+
+    if config.balance.anchor.is_some() {
+        match flextime(tsv, config, today) {
+            Ok(Flextime {
+                anchor,
+                targets,
+                worked,
+            }) => {
+                let days = idid::balance::days(&worked, anchor, today, &targets);
+                let over = days
+                    .iter()
+                    .fold(Duration::zero(), |sum, day| sum + day.over());
+                println!("Balance {} since {}", idid::balance::signed(&over), anchor);
+            }
+            Err(e) => eprintln!("Error: {}", e),
+        }
+    }
+
+    if spark {
+        let days = 14;
+        let first = today - Duration::days(days - 1);
+        let filter = with_calendar(idid::DateFilter::new(&[first, today], &[]), calendar);
+        let entries: Vec<idid::Entry> = idid::pick(tsv, &filter).collect();
+        let group = idid::report::Group::Day;
+        let rows = idid::report::totals(&entries, group, calendar)
+            .and_then(|rows| idid::report::fill(rows, filter.days(), group, calendar))
+            .expect("days are a period");
+        let totals: Vec<Duration> = rows.iter().map(|row| row.duration).collect();
+        println!(
+            "Last {} days {}",
+            days,
+            idid::chart::sparkline(&totals, unicode)
+        );
+    }
+}
+
+/// Time worked per day since the [balance] anchor and the targets to compare
+struct Flextime {
+    anchor: NaiveDate,
+    targets: idid::balance::Targets,
+    worked: std::collections::BTreeMap<NaiveDate, Duration>,
+}
+
+/// Days worked from the [balance] anchor through today
+fn flextime(tsv: &str, config: &idid::Config, today: NaiveDate) -> Result<Flextime, String> {
+    let balance = &config.balance;
+    let anchor = balance
+        .anchor
+        .ok_or("balance needs an anchor in the [balance] config")?;
+    let holidays = match &balance.holidays {
+        Some(path) => idid::holidays::load(path)?,
+        None => Vec::new(),
+    };
+    let targets = balance.targets(&holidays)?;
+    let filter = with_calendar(
+        idid::DateFilter::new(&[anchor, today], &[]),
+        &config.calendar,
+    );
+    let entries: Vec<idid::Entry> = idid::pick(tsv, &filter).collect();
+    Ok(Flextime {
+        anchor,
+        targets,
+        worked: idid::balance::worked(&entries, &config.calendar),
+    })
+}
+
+/// Write worked, target, and difference per week or day, then the running balance
+fn command_balance(tsv: &str, config: &idid::Config, per_day: bool) {
+    let today = date_parse::today(&config.calendar);
+    let Flextime {
+        anchor,
+        targets,
+        worked,
+    } = flextime(tsv, config, today).unwrap_or_else(|e| {
+        eprintln!("Error: {}", e);
+        std::process::exit(2);
+    });
+    let rows = match per_day {
+        true => idid::balance::days(&worked, anchor, today, &targets),
+        false => idid::balance::weeks(&worked, anchor, today, &targets, config.calendar.week_start),
+    };
+    let mut over = Duration::zero();
+    for row in &rows {
+        over += row.over();
+        println!(
+            "{}\t{}\t{}\t{}",
+            row.label,
+            idid::hh_mm(&row.worked),
+            idid::hh_mm(&row.target),
+            idid::balance::signed(&row.over())
+        );
+    }
+    println!("Balance\t{}", idid::balance::signed(&over));
+}
+
+/// Report TSV issues and exit 1 when any is an error
+fn command_check(tsv: &str, config: &idid::Config) {
+    let file = fs::File::open(tsv).unwrap_or_else(|e| {
+        eprintln!("Error: {}: {}", tsv, e);
+        std::process::exit(2);
+    });
+    let issues = idid::check::check(io::BufReader::new(file), config.calendar.timezone);
+    for issue in &issues {
+        println!("{}", issue);
+    }
+    if issues
+        .iter()
+        .any(|issue| issue.severity == idid::check::Severity::Error)
+    {
+        std::process::exit(1);
+    }
+}
+
+fn command_show(
+    tsv: &str,
+    config: &idid::Config,
+    args: &ArgsShow,
+    output: &ArgsOutput,
+    round: &ArgsRound,
+) {
+    let filter = date_filter_from_date_args(args, &config.calendar);
+    if filter.is_empty() {
+        eprintln!("Error: at least one of --dates or --range is required");
+        std::process::exit(1);
+    }
+    let picked = idid::pick(tsv, &filter);
+    let entries: Vec<idid::Entry> = match &output.between {
+        Some(window) => picked
+            .flat_map(|entry| window.clip(&entry).into_iter().rev())
+            .collect(),
+        None => picked.collect(),
+    };
+    let durations = shown_durations(&entries, round);
+
+    if output.json {
+        let envelope = idid::json::Envelope::with_durations(&entries, &durations);
+        println!("{}", serde_json::to_string(&envelope).expect("JSON output"));
+        return;
+    }
+
+    let mut total_duration = Duration::zero();
+    for (entry, duration) in entries.iter().zip(&durations) {
+        total_duration += *duration;
+        if output.json_lines {
+            let record = idid::json::Record::from(entry).with_duration(duration);
+            let line = idid::json::Line::record(record);
+            println!("{}", serde_json::to_string(&line).expect("JSON output"));
+        } else {
+            println!("{}", entry.serialize_duration(duration, &output.seconds));
+        }
+    }
+    if output.total && output.json_lines {
+        let line = idid::json::Line::total(&total_duration);
+        println!("{}", serde_json::to_string(&line).expect("JSON output"));
+    } else if output.total && !output.seconds && total_duration > Duration::zero() {
+        println!(
+            "                Total    \t{}",
+            idid::hh_mm(&total_duration)
+        );
+    }
+}
+
+/// Durations of newest first entries, rounded oldest first when asked
+fn shown_durations(entries: &[idid::Entry], round: &ArgsRound) -> Vec<Duration> {
+    let durations: Vec<Duration> = entries.iter().rev().map(|entry| entry.duration()).collect();
+    let mut shown = match round.rounding() {
+        Some(rounding) => rounding.round_all(&durations, round.carry),
+        None => durations,
+    };
+    shown.reverse();
+    shown
+}
+
+/// Write the total of each group and the overall total
+fn command_report(
+    tsv: &str,
+    config: &idid::Config,
+    args: &ArgsShow,
+    by: &ReportBy,
+    round: &ArgsRound,
+    round_per: &RoundPer,
+    chart: Option<Chart>,
+) {
+    let filter = date_filter_from_date_args(args, &config.calendar);
+    let entries: Vec<idid::Entry> = idid::pick(tsv, &filter).collect();
+    let group = (*by).into();
+    // Periods without time through today, when there is a beginning
+    let today = date_parse::today(&config.calendar);
+    let days = filter
+        .oldest_date
+        .map(|_| filter.days().take_while(|day| *day <= today))
+        .into_iter()
+        .flatten();
+    let totals = |group| match round.rounding() {
+        Some(rounding) => idid::report::rounded_totals(
+            &entries,
+            group,
+            &config.calendar,
+            &rounding,
+            (*round_per).into(),
+            round.carry,
+        ),
+        None => idid::report::totals(&entries, group, &config.calendar),
+    };
+    let rows = totals(group)
+        .and_then(|rows| idid::report::fill(rows, days, group, &config.calendar))
+        .unwrap_or_else(|e| {
+            eprintln!("Error: {}", e);
+            std::process::exit(2);
+        });
+    match chart {
+        Some(chart) => {
+            let width = idid::chart::terminal_width();
+            for line in idid::chart::bars(&rows, width, chart.unicode) {
+                println!("{}", line);
+            }
+        }
+        None => {
+            for row in &rows {
+                println!("{}\t{}", row.label, idid::hh_mm(&row.duration));
+            }
+        }
+    }
+    // The rows as shown, so an entry with two tags counts in both
+    let total = rows
+        .iter()
+        .fold(Duration::zero(), |sum, row| sum + row.duration);
+    println!("Total\t{}", idid::hh_mm(&total));
+}
+
+/// Write interruptions and focus per day, the totals, and the top sources
+fn command_interruptions(tsv: &str, config: &idid::Config, args: &ArgsShow) {
+    let filter = date_filter_from_date_args(args, &config.calendar);
+    let entries: Vec<idid::Entry> = idid::pick(tsv, &filter).collect();
+    let (days, sources) =
+        idid::interruptions::analyze(&entries, &config.interruptions, &config.calendar);
+    let mut count = 0;
+    let mut lost = Duration::zero();
+    println!("Day\tInterruptions\tLost\tLongest focus");
+    for day in &days {
+        count += day.count;
+        lost += day.lost;
+        println!(
+            "{}\t{}\t{}\t{}",
+            day.date,
+            day.count,
+            idid::hh_mm(&day.lost),
+            idid::hh_mm(&day.longest_focus)
+        );
+    }
+    println!("Total\t{}\t{}", count, idid::hh_mm(&lost));
+    if !sources.is_empty() {
+        println!("Top sources");
+    }
+    for source in sources.iter().take(5) {
+        println!(
+            "  {}\t{}\t{}",
+            source.name,
+            source.count,
+            idid::hh_mm(&source.lost)
+        );
+    }
+}
+
+/// Write day lengths, start and stop times, long entries, and tag shares
+fn command_stats(
+    tsv: &str,
+    config: &idid::Config,
+    args: &ArgsShow,
+    short: &Duration,
+    top: usize,
+    json: bool,
+) {
+    let filter = date_filter_from_date_args(args, &config.calendar);
+    let entries: Vec<idid::Entry> = idid::pick(tsv, &filter).collect();
+    let stats = idid::stats::stats(&entries, &config.calendar, *short, top);
+    if json {
+        let summary = idid::json::Summary::from(&stats);
+        println!("{}", serde_json::to_string(&summary).expect("JSON output"));
+        return;
+    }
+
+    let time = |time: Option<chrono::NaiveTime>| {
+        time.map_or("-".to_string(), |t| t.format("%H:%M").to_string())
+    };
+    println!("Days tracked\t{}", stats.days);
+    println!("Total\t{}", idid::hh_mm(&stats.total));
+    println!("Average day\t{}", idid::hh_mm(&stats.average_day));
+    println!("Median day\t{}", idid::hh_mm(&stats.median_day));
+    println!("Typical start\t{}", time(stats.typical_start));
+    println!("Typical stop\t{}", time(stats.typical_stop));
+    println!(
+        "Short entries\t{:.1} per day; {} under {}",
+        stats.short_per_day(),
+        stats.short_entries,
+        idid::hh_mm(short)
+    );
+    println!("Longest entries");
+    for entry in &stats.longest {
+        println!("  {}", entry.serialize(&false, false));
+    }
+    println!("Tags");
+    for share in &stats.tags {
+        println!(
+            "  {}\t{}\t{:.1}%",
+            share.tag,
+            idid::hh_mm(&share.duration),
+            share.percent
+        );
+    }
+}
+
+/// Draw a year of daily totals in the terminal or to an SVG file
+fn command_heatmap(
+    tsv: &str,
+    config: &idid::Config,
+    year: Option<i32>,
+    svg: Option<&std::path::Path>,
+    unicode: bool,
+) {
+    let calendar = &config.calendar;
+    let year = year.unwrap_or_else(|| date_parse::today(calendar).year());
+    let (Some(first), Some(last)) = (
+        NaiveDate::from_ymd_opt(year, 1, 1),
+        NaiveDate::from_ymd_opt(year, 12, 31),
+    ) else {
+        eprintln!("Error: invalid year {}", year);
+        std::process::exit(2);
+    };
+    let filter = with_calendar(idid::DateFilter::new(&[first, last], &[]), calendar);
+    let entries: Vec<idid::Entry> = idid::pick(tsv, &filter).collect();
+    let heatmap = idid::heatmap::Heatmap::new(year, &entries, calendar);
+    match svg {
+        Some(path) => fs::write(path, heatmap.svg()).unwrap_or_else(|e| {
+            eprintln!("Error: {}: {}", path.display(), e);
+            std::process::exit(2);
+        }),
+        None => print!("{}", heatmap.text(unicode)),
+    }
+}
+
+/// Write entries in an outline format
+fn command_export(tsv: &str, config: &idid::Config, args: &ArgsShow) {
+    let filter = date_filter_from_date_args(args, &config.calendar);
+    if filter.is_empty() {
+        eprintln!("Error: at least one of --dates or --range is required");
+        std::process::exit(1);
+    }
+    let entries: Vec<idid::Entry> = idid::pick(tsv, &filter).collect();
+    print!("{}", idid::export::org(&entries, &config.calendar));
+}
+
 fn command_start(tsv: &str, offset: &Option<String>, quiet: &bool) {
-    match offset_from_current_or_current(offset.as_deref()) {
+    match offset_from_current_or_current(tsv, offset.as_deref()) {
         Ok(ended) => {
             idid::write_to_tsv(tsv, &ended, None);
             if !quiet {
@@ -247,7 +1098,7 @@ fn command_start(tsv: &str, offset: &Option<String>, quiet: &bool) {
     }
 }
 
-fn command_last(tsv: &str, lines: &Option<u32>) {
+fn command_last(tsv: &str, config: &idid::Config, lines: &Option<u32>) {
     if lines.is_some() {
         let file = fs::File::open(tsv).expect("Failed to open TSV file");
         let mut reverse_buffer = rev_lines::RevLines::new(file);
@@ -259,9 +1110,13 @@ fn command_last(tsv: &str, lines: &Option<u32>) {
             }
         }
     } else {
-        let timestamp = get_last_entry_timestamp(tsv).expect("Invalid TSV");
+        let timestamp = get_last_entry_timestamp(tsv).unwrap_or_else(|e| {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        });
         let now = current_datetime();
-        if now.date_naive() == timestamp.date_naive() {
+        let calendar = &config.calendar;
+        if calendar.workday(now) == calendar.workday(timestamp) {
             let elapsed = now - timestamp;
             println!(
                 "{:>02}:{:>02}",
@@ -269,44 +1124,283 @@ fn command_last(tsv: &str, lines: &Option<u32>) {
                 elapsed.num_minutes() % 60
             );
         } else {
-            eprintln!("Nothing today but {}", timestamp.date_naive());
+            eprintln!("Nothing today but {}", calendar.workday(timestamp));
             std::process::exit(3);
         }
     }
 }
 
+/// Bill a client's entries at the configured rates
+fn command_invoice(
+    tsv: &str,
+    config: &idid::Config,
+    args: &ArgsShow,
+    client: &str,
+    format: &InvoiceFormat,
+    draft: &bool,
+) {
+    let filter = date_filter_from_date_args(args, &config.calendar);
+    let entries: Vec<idid::Entry> = idid::pick(tsv, &filter).collect();
+
+    // Check the invoice before taking a number
+    let issued = current_datetime().date_naive();
+    let mut invoice =
+        idid::invoice::Invoice::build(&entries, client, &config.invoice, "DRAFT", issued)
+            .unwrap_or_else(|e| {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            });
+    if !draft {
+        let sequence = std::path::Path::new(tsv).with_file_name("invoice-number");
+        let number = idid::invoice::next_number(&sequence).unwrap_or_else(|e| {
+            eprintln!("Error: {}", e);
+            std::process::exit(2);
+        });
+        invoice.number = format!("{}{:04}", config.invoice.number_prefix, number);
+    }
+
+    match format {
+        InvoiceFormat::Markdown => print!("{}", invoice.to_markdown()),
+        InvoiceFormat::Html => print!("{}", invoice.to_html()),
+        InvoiceFormat::Csv => match invoice.to_csv() {
+            Ok(csv) => print!("{}", csv),
+            Err(e) => {
+                eprintln!("Error: {}", e);
+                std::process::exit(2);
+            }
+        },
+    }
+}
+
+/// Merge entries from another time tracker into the TSV
+fn command_import(tsv: &str, from: &ImportFrom, file: &PathBuf, dry_run: &bool, force: &bool) {
+    let intervals = fs::File::open(file)
+        .map_err(|e| format!("{}: {}", file.display(), e))
+        .and_then(|reader| match from {
+            ImportFrom::Toggl => idid::import::from_csv(reader, idid::import::Source::Toggl),
+            ImportFrom::Clockify => idid::import::from_csv(reader, idid::import::Source::Clockify),
+            ImportFrom::Watson => idid::import::from_watson(reader),
+        })
+        .unwrap_or_else(|e| {
+            eprintln!("Error: {}", e);
+            std::process::exit(2);
+        });
+
+    let (lines, issues) = idid::import::to_lines(&intervals);
+    let file = fs::File::open(tsv).unwrap_or_else(|e| {
+        eprintln!("Error: {}: {}", tsv, e);
+        std::process::exit(2);
+    });
+    let journal: Vec<idid::Entry> =
+        idid::EntryIterator::new(io::BufReader::new(file), |_: &idid::Entry| true, None).collect();
+    let overlaps = idid::import::journal_overlaps(&intervals, &journal);
+    for issue in &issues {
+        eprintln!("WARNING: {}", issue);
+    }
+    if *force || *dry_run {
+        for overlap in &overlaps {
+            eprintln!("WARNING: {}", overlap);
+        }
+    } else if !overlaps.is_empty() {
+        for overlap in &overlaps {
+            eprintln!("Error: {}", overlap);
+        }
+        eprintln!("Error: nothing imported; use --force to merge anyway");
+        std::process::exit(2);
+    }
+
+    if *dry_run {
+        for entry in idid::import::preview(&lines) {
+            println!("{}", entry.serialize(&false, false));
+        }
+        return;
+    }
+
+    match idid::merge_into_tsv(tsv, &lines, *force) {
+        Ok(added) => println!("Imported {} of {} lines.", added, lines.len()),
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            std::process::exit(2);
+        }
+    }
+}
+
+fn parse_zone(zone: &str) -> Result<chrono_tz::Tz, String> {
+    zone.parse().map_err(|_| {
+        format!(
+            "unknown zone {:?}; use an IANA name like \"America/Chicago\"",
+            zone
+        )
+    })
+}
+
+/// Read the config file, if any, or exit with the reason it is unusable.
+///
+/// Sections only some commands read are checked by those commands with
+/// `require_section`.
+fn load_config(config: &Option<PathBuf>) -> idid::Config {
+    let loaded = match idid::get_config_path(config) {
+        Ok(Some(path)) => idid::Config::load(&path),
+        Ok(None) => Ok(idid::Config::default()),
+        Err(e) => Err(e.to_string()),
+    };
+    loaded.unwrap_or_else(|e| {
+        eprintln!("Error: {}", e);
+        std::process::exit(2);
+    })
+}
+
+/// Exit with the reason a config section the command reads is unusable.
+fn require_section(config: &idid::Config, name: &str) {
+    if let Err(e) = config.section(name) {
+        eprintln!("Error: {}", e);
+        std::process::exit(2);
+    }
+}
+
 /// Get either an offset from current time or the current time.
-fn offset_from_current_or_current(offset: Option<&str>) -> Result<DateTime<FixedOffset>, String> {
+///
+/// An offset of "last+DURATION" is after the last entry instead.
+fn offset_from_current_or_current(
+    tsv: &str,
+    offset: Option<&str>,
+) -> Result<DateTime<FixedOffset>, String> {
+    if let Some(took) = offset.and_then(|offset| offset.strip_prefix("last+")) {
+        return after_last_entry(tsv, took);
+    }
     if offset.is_some() {
         return time_parse::time_adjustment(offset);
     }
     Ok(current_datetime())
 }
 
+/// The last entry's timestamp plus DURATION or minutes, but not in the future.
+fn after_last_entry(tsv: &str, took: &str) -> Result<DateTime<FixedOffset>, String> {
+    let duration = match took.parse::<i64>() {
+        Ok(minutes) => {
+            Duration::try_minutes(minutes).ok_or(format!("Invalid duration {:?}", took))?
+        }
+        Err(_) => time_parse::duration_from_str(took)?,
+    };
+    if duration <= Duration::zero() {
+        return Err(format!("Invalid duration {:?}", took));
+    }
+    let now = current_datetime();
+    let ended = get_last_entry_timestamp(tsv)?
+        .checked_add_signed(duration)
+        .ok_or(format!("Invalid duration {:?}", took))?
+        .with_timezone(now.offset());
+    if ended > now {
+        return Err(format!("{} is in the future", ended.format("%a %I:%M %p")));
+    }
+    Ok(ended)
+}
+
+/// The timestamp of the last line, or "No entries" for an empty TSV.
 fn get_last_entry_timestamp(tsv: &str) -> Result<DateTime<FixedOffset>, String> {
-    let file = fs::File::open(tsv).expect("Failed to open TSV file");
+    let file = fs::File::open(tsv).map_err(|e| format!("{}: {}", tsv, e))?;
     let mut reverse_buffer = rev_lines::RevLines::new(file);
-    match reverse_buffer.next().expect("empty TSV") {
-        Ok(tsv_line) => {
+    match reverse_buffer.next() {
+        Some(Ok(tsv_line)) => {
             let (timestamp, _) = idid::Entry::from_tsv(&tsv_line)?;
             Ok(timestamp)
         }
-        Err(e) => panic!("{}", e),
+        Some(Err(e)) => Err(format!("{}: {}", tsv, e)),
+        None => Err("No entries".to_string()),
+    }
+}
+
+/// Pick days in the calendar timezone with workdays starting at day_starts_at
+fn with_calendar(filter: idid::DateFilter, calendar: &Calendar) -> idid::DateFilter {
+    let filter = filter.day_starts_at(calendar.day_starts_at);
+    match calendar.timezone {
+        Some(zone) => filter.in_zone(zone),
+        None => filter,
     }
 }
 
-/// Process dates and ranges using str_to_date
-fn date_filter_from_date_args(args: &ArgsShow) -> idid::DateFilter {
-    let mut parsed_dates =
-        date_parse::strings_to_dates(&args.dates).expect("Unable to parse dates.");
-    let parsed_range = date_parse::strings_to_dates(&args.range).expect("Unable to parse range.");
+/// Process dates and ranges using span_from_str
+fn date_filter_from_date_args(args: &ArgsShow, calendar: &Calendar) -> idid::DateFilter {
+    let spans = date_parse::strings_to_spans(&args.dates, calendar).unwrap_or_else(|e| {
+        eprintln!("Error: {}", e);
+        std::process::exit(2);
+    });
+    let range = date_parse::strings_to_spans(&args.range, calendar).unwrap_or_else(|e| {
+        eprintln!("Error: --range: {}", e);
+        std::process::exit(2);
+    });
 
-    if parsed_dates.len() == 0 && parsed_range.len() == 0 {
-        #[cfg(debug_assertions)]
-        println!("ArgsShow adding default of today");
-        parsed_dates.push(date_parse::date_from_str("today").unwrap());
+    let mut parsed_dates = Vec::new();
+    let mut parsed_range = Vec::new();
+    for (first, last) in spans {
+        if first == last {
+            parsed_dates.push(first);
+        } else {
+            parsed_range.extend([first, last]);
+        }
+    }
+    // Periods as range ends cover both periods in either order
+    if let [begin, end] = range.as_slice() {
+        parsed_range.extend([begin.0.min(end.0), begin.1.max(end.1)]);
+    }
+
+    // Half-open when only one is given
+    if args.since.is_some() || args.until.is_some() {
+        let since = args
+            .since
+            .as_ref()
+            .map(|since| date_parse::span_from_str(since, calendar));
+        let until = args
+            .until
+            .as_ref()
+            .map(|until| date_parse::span_from_str(until, calendar));
+        let since = since.transpose().unwrap_or_else(|e| {
+            eprintln!("Error: --since: {}", e);
+            std::process::exit(2);
+        });
+        let until = until.transpose().unwrap_or_else(|e| {
+            eprintln!("Error: --until: {}", e);
+            std::process::exit(2);
+        });
+        parsed_range.extend([
+            since.map_or(NaiveDate::MIN, |(first, _)| first),
+            until.map_or(NaiveDate::MAX, |(_, last)| last),
+        ]);
+    }
+
+    if parsed_dates.is_empty() && parsed_range.is_empty() {
+        parsed_dates.push(date_parse::date_from_str("today", calendar).unwrap());
+    }
+    let mut filter = idid::DateFilter::new(&parsed_range, &parsed_dates);
+    if args.except.is_some() {
+        let except = date_parse::strings_to_spans(&args.except, calendar).unwrap_or_else(|e| {
+            eprintln!("Error: --except: {}", e);
+            std::process::exit(2);
+        });
+        let except: Vec<NaiveDate> = except
+            .into_iter()
+            .flat_map(|(first, last)| [first, last])
+            .collect();
+        filter = filter.difference(&idid::DateFilter::new(&except, &[]));
+    }
+    if args.weekdays {
+        filter = filter.weekdays();
+    }
+    if args.weekends {
+        filter = filter.weekends();
+    }
+    filter = with_calendar(filter, calendar);
+    if args.split_days {
+        filter = filter.split_days();
+    }
+    if let Some(path) = &args.exclude_holidays {
+        let holidays = idid::holidays::load(path).unwrap_or_else(|e| {
+            eprintln!("Error: {}", e);
+            std::process::exit(2);
+        });
+        filter = filter.excluding(&holidays);
     }
-    idid::DateFilter::new(&parsed_range, &parsed_dates)
+    filter
 }
 
 fn praise() {
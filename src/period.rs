@@ -0,0 +1,313 @@
+use chrono::{DateTime, Datelike, Duration, FixedOffset, Months, NaiveDate, NaiveTime, Weekday};
+use chrono_tz::Tz;
+use serde::Deserialize;
+
+/// The `[calendar]` section of the configuration.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default)]
+pub struct Calendar {
+    /// First day of the week, ie "mon" or "sun".
+    pub week_start: Weekday,
+    /// How payroll divides time, if at all.
+    pub pay_period: Option<PayPeriod>,
+    /// Month 1 to 12 starting the fiscal year, ie 10 for October.
+    pub fiscal_year_start: u32,
+    /// IANA zone, ie "America/Chicago" or "UTC", for days and display.
+    pub timezone: Option<Tz>,
+    /// When a workday begins, ie "18:00" for a night shift; midnight by default.
+    #[serde(deserialize_with = "crate::config::time")]
+    pub day_starts_at: NaiveTime,
+}
+
+impl Default for Calendar {
+    fn default() -> Self {
+        Self {
+            week_start: Weekday::Mon,
+            pay_period: None,
+            fiscal_year_start: 1,
+            timezone: None,
+            day_starts_at: NaiveTime::MIN,
+        }
+    }
+}
+
+/// Pay period kinds from `pay_period = { kind = "biweekly", anchor = 2024-01-05 }`.
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(tag = "kind", rename_all = "kebab-case")]
+pub enum PayPeriod {
+    /// Weeks beginning on the calendar week_start.
+    Weekly,
+    /// Two weeks at a time from the first day of any pay period.
+    Biweekly {
+        #[serde(deserialize_with = "crate::config::date")]
+        anchor: NaiveDate,
+    },
+    /// The 1st to the 15th and the 16th to the end of the month.
+    SemiMonthly,
+}
+
+impl Calendar {
+    /// The workday of a moment, in the timezone when there is one.
+    ///
+    /// With day_starts_at of 18:00, Tuesday 03:00 is still Monday's workday.
+    pub fn workday(&self, when: DateTime<FixedOffset>) -> NaiveDate {
+        let when = match self.timezone {
+            Some(zone) => when.with_timezone(&zone).fixed_offset(),
+            None => when,
+        };
+        workday(&when, self.day_starts_at)
+    }
+
+    /// First and last day of the pay period containing the date.
+    ///
+    /// # Returns
+    /// None when there is no pay_period.
+    pub fn pay_period(&self, date: NaiveDate) -> Option<(NaiveDate, NaiveDate)> {
+        match self.pay_period.as_ref()? {
+            PayPeriod::Weekly => Some(week(date, self.week_start)),
+            PayPeriod::Biweekly { anchor } => {
+                let periods = (date - *anchor).num_days().div_euclid(14);
+                let first = *anchor + Duration::days(periods * 14);
+                Some((first, first + Duration::days(13)))
+            }
+            PayPeriod::SemiMonthly => {
+                let (first, last) = month(date.year(), date.month())?;
+                let middle = first + Duration::days(14);
+                match date <= middle {
+                    true => Some((first, middle)),
+                    false => Some((middle + Duration::days(1), last)),
+                }
+            }
+        }
+    }
+
+    /// The fiscal year containing the date, named by the year it ends in.
+    ///
+    /// Starting in October, 2024-10-01 is in fiscal year 2025.
+    pub fn fiscal_year_of(&self, date: NaiveDate) -> i32 {
+        match self.fiscal_year_start > 1 && date.month() >= self.fiscal_year_start {
+            true => date.year() + 1,
+            false => date.year(),
+        }
+    }
+
+    /// First and last day of the fiscal year.
+    pub fn fiscal_year(&self, fiscal_year: i32) -> Option<(NaiveDate, NaiveDate)> {
+        self.fiscal_months(fiscal_year, 0, 12)
+    }
+
+    /// First and last day of the fiscal quarter 1 to 4.
+    pub fn fiscal_quarter(&self, fiscal_year: i32, quarter: u32) -> Option<(NaiveDate, NaiveDate)> {
+        if !(1..=4).contains(&quarter) {
+            return None;
+        }
+        self.fiscal_months(fiscal_year, (quarter - 1) * 3, 3)
+    }
+
+    /// The fiscal year and quarter 1 to 4 containing the date.
+    pub fn fiscal_quarter_of(&self, date: NaiveDate) -> (i32, u32) {
+        let months_in = (date.month() + 12 - self.fiscal_year_start.clamp(1, 12)) % 12;
+        (self.fiscal_year_of(date), months_in / 3 + 1)
+    }
+
+    /// Span of `count` months starting `skip` months into the fiscal year.
+    fn fiscal_months(
+        &self,
+        fiscal_year: i32,
+        skip: u32,
+        count: u32,
+    ) -> Option<(NaiveDate, NaiveDate)> {
+        if !(1..=12).contains(&self.fiscal_year_start) {
+            return None;
+        }
+        let year = match self.fiscal_year_start {
+            1 => fiscal_year,
+            _ => fiscal_year - 1,
+        };
+        let first = NaiveDate::from_ymd_opt(year, self.fiscal_year_start, 1)?
+            .checked_add_months(Months::new(skip))?;
+        let next = first.checked_add_months(Months::new(count))?;
+        Some((first, next - Duration::days(1)))
+    }
+}
+
+/// The workday of a moment in its own offset, when workdays begin at day_start.
+pub fn workday(when: &DateTime<FixedOffset>, day_start: NaiveTime) -> NaiveDate {
+    (*when - (day_start - NaiveTime::MIN)).date_naive()
+}
+
+/// First and last day of the week containing the date.
+pub fn week(date: NaiveDate, week_start: Weekday) -> (NaiveDate, NaiveDate) {
+    let days_back =
+        (date.weekday().num_days_from_monday() + 7 - week_start.num_days_from_monday()) % 7;
+    let first = date - Duration::days(days_back.into());
+    (first, first + Duration::days(6))
+}
+
+/// First and last day of the ISO 8601 week, which always starts on Monday.
+pub fn iso_week(year: i32, week: u32) -> Option<(NaiveDate, NaiveDate)> {
+    let first = NaiveDate::from_isoywd_opt(year, week, Weekday::Mon)?;
+    Some((first, first + Duration::days(6)))
+}
+
+/// First and last day of the month.
+pub fn month(year: i32, month: u32) -> Option<(NaiveDate, NaiveDate)> {
+    let first = NaiveDate::from_ymd_opt(year, month, 1)?;
+    let next = match month {
+        12 => NaiveDate::from_ymd_opt(year + 1, 1, 1)?,
+        _ => NaiveDate::from_ymd_opt(year, month + 1, 1)?,
+    };
+    Some((first, next - Duration::days(1)))
+}
+
+/// First and last day of the calendar quarter 1 to 4.
+pub fn quarter(year: i32, quarter: u32) -> Option<(NaiveDate, NaiveDate)> {
+    if !(1..=4).contains(&quarter) {
+        return None;
+    }
+    let (first, _) = month(year, quarter * 3 - 2)?;
+    let (_, last) = month(year, quarter * 3)?;
+    Some((first, last))
+}
+
+/// First and last day of the year.
+pub fn year(year: i32) -> Option<(NaiveDate, NaiveDate)> {
+    Some((
+        NaiveDate::from_ymd_opt(year, 1, 1)?,
+        NaiveDate::from_ymd_opt(year, 12, 31)?,
+    ))
+}
+
+/// The calendar quarter 1 to 4 containing the date.
+pub fn quarter_of(date: NaiveDate) -> u32 {
+    (date.month() - 1) / 3 + 1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::date_filter::ymd;
+
+    #[test]
+    fn test_week() {
+        // Wednesday
+        let date = ymd(2024, 4, 3);
+        assert_eq!(week(date, Weekday::Mon), (ymd(2024, 4, 1), ymd(2024, 4, 7)));
+        assert_eq!(
+            week(date, Weekday::Sun),
+            (ymd(2024, 3, 31), ymd(2024, 4, 6))
+        );
+        assert_eq!(week(date, Weekday::Wed), (ymd(2024, 4, 3), ymd(2024, 4, 9)));
+    }
+
+    #[test]
+    fn test_periods() {
+        assert_eq!(iso_week(2024, 14), Some((ymd(2024, 4, 1), ymd(2024, 4, 7))));
+        assert_eq!(iso_week(2024, 54), None);
+        assert_eq!(month(2024, 2), Some((ymd(2024, 2, 1), ymd(2024, 2, 29))));
+        assert_eq!(month(2024, 12), Some((ymd(2024, 12, 1), ymd(2024, 12, 31))));
+        assert_eq!(quarter(2024, 2), Some((ymd(2024, 4, 1), ymd(2024, 6, 30))));
+        assert_eq!(quarter(2024, 5), None);
+        assert_eq!(year(2024), Some((ymd(2024, 1, 1), ymd(2024, 12, 31))));
+        assert_eq!(quarter_of(ymd(2024, 12, 31)), 4);
+    }
+
+    #[test]
+    fn test_workday() {
+        let when = |text| DateTime::parse_from_rfc3339(text).unwrap();
+        let mut calendar = Calendar::default();
+        assert_eq!(
+            calendar.workday(when("2024-04-02T03:00:00Z")),
+            ymd(2024, 4, 2)
+        );
+
+        calendar.day_starts_at = NaiveTime::from_hms_opt(18, 0, 0).unwrap();
+        assert_eq!(
+            calendar.workday(when("2024-04-02T03:00:00Z")),
+            ymd(2024, 4, 1)
+        );
+        assert_eq!(
+            calendar.workday(when("2024-04-02T17:59:00Z")),
+            ymd(2024, 4, 1)
+        );
+        assert_eq!(
+            calendar.workday(when("2024-04-02T18:00:00Z")),
+            ymd(2024, 4, 2)
+        );
+
+        calendar.timezone = Some(chrono_tz::Asia::Tokyo);
+        assert_eq!(
+            calendar.workday(when("2024-04-02T03:00:00Z")),
+            ymd(2024, 4, 1)
+        );
+        assert_eq!(
+            calendar.workday(when("2024-04-02T09:00:00Z")),
+            ymd(2024, 4, 2)
+        );
+    }
+
+    #[test]
+    fn test_pay_period() {
+        let mut calendar = Calendar::default();
+        assert_eq!(calendar.pay_period(ymd(2024, 4, 3)), None);
+
+        calendar.pay_period = Some(PayPeriod::Weekly);
+        assert_eq!(
+            calendar.pay_period(ymd(2024, 4, 3)),
+            Some((ymd(2024, 4, 1), ymd(2024, 4, 7)))
+        );
+
+        calendar.pay_period = Some(PayPeriod::Biweekly {
+            anchor: ymd(2024, 3, 29),
+        });
+        assert_eq!(
+            calendar.pay_period(ymd(2024, 4, 11)),
+            Some((ymd(2024, 3, 29), ymd(2024, 4, 11)))
+        );
+        assert_eq!(
+            calendar.pay_period(ymd(2024, 4, 12)),
+            Some((ymd(2024, 4, 12), ymd(2024, 4, 25)))
+        );
+        assert_eq!(
+            calendar.pay_period(ymd(2024, 3, 28)),
+            Some((ymd(2024, 3, 15), ymd(2024, 3, 28)))
+        );
+
+        calendar.pay_period = Some(PayPeriod::SemiMonthly);
+        assert_eq!(
+            calendar.pay_period(ymd(2024, 2, 15)),
+            Some((ymd(2024, 2, 1), ymd(2024, 2, 15)))
+        );
+        assert_eq!(
+            calendar.pay_period(ymd(2024, 2, 16)),
+            Some((ymd(2024, 2, 16), ymd(2024, 2, 29)))
+        );
+    }
+
+    #[test]
+    fn test_fiscal_year() {
+        let calendar = Calendar {
+            fiscal_year_start: 10,
+            ..Calendar::default()
+        };
+        assert_eq!(
+            calendar.fiscal_year(2025),
+            Some((ymd(2024, 10, 1), ymd(2025, 9, 30)))
+        );
+        assert_eq!(
+            calendar.fiscal_quarter(2025, 1),
+            Some((ymd(2024, 10, 1), ymd(2024, 12, 31)))
+        );
+        assert_eq!(
+            calendar.fiscal_quarter(2025, 2),
+            Some((ymd(2025, 1, 1), ymd(2025, 3, 31)))
+        );
+        assert_eq!(calendar.fiscal_quarter(2025, 5), None);
+        assert_eq!(calendar.fiscal_quarter_of(ymd(2024, 10, 1)), (2025, 1));
+        assert_eq!(calendar.fiscal_quarter_of(ymd(2025, 9, 30)), (2025, 4));
+
+        let calendar = Calendar::default();
+        assert_eq!(calendar.fiscal_year(2025), year(2025));
+        assert_eq!(calendar.fiscal_quarter_of(ymd(2025, 4, 1)), (2025, 2));
+    }
+}
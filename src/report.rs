@@ -0,0 +1,311 @@
+use chrono::{Datelike, Duration, NaiveDate};
+use std::collections::BTreeMap;
+
+use crate::entry::Entry;
+use crate::period::{self, Calendar};
+use crate::rounding::Rounding;
+
+/// How to group entries in a report.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Group {
+    Day,
+    Week,
+    Month,
+    Quarter,
+    Year,
+    /// The configured pay period
+    PayPeriod,
+    /// Quarters of the configured fiscal year
+    FiscalQuarter,
+    /// Each "+" or "@" tag; an entry with two tags counts toward both
+    Tag,
+}
+
+/// Total time for one group.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Row {
+    pub label: String,
+    pub duration: Duration,
+}
+
+/// What a report rounds to the increment.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum RoundPer {
+    /// Each entry before it is added to a group
+    #[default]
+    Entry,
+    /// The time of each group on each workday
+    Day,
+}
+
+/// Label for untagged entries when grouping by tag.
+pub const UNTAGGED: &str = "(untagged)";
+
+/// Label of the group containing the date.
+///
+/// Labels of periods are themselves a DATE, ie "2024-Q2", "fy2025q1", or
+/// "2024-03-29..2024-04-11", so they can be given to `show`.
+pub fn period_label(group: Group, date: NaiveDate, calendar: &Calendar) -> Result<String, String> {
+    let span = |(first, last): (NaiveDate, NaiveDate)| format!("{}..{}", first, last);
+    let label = match group {
+        Group::Day => date.to_string(),
+        Group::Week => span(period::week(date, calendar.week_start)),
+        Group::Month => date.format("%Y-%m").to_string(),
+        Group::Quarter => format!("{}-Q{}", date.year(), period::quarter_of(date)),
+        Group::Year => date.year().to_string(),
+        Group::PayPeriod => span(
+            calendar
+                .pay_period(date)
+                .ok_or("grouping by pay period needs a pay_period in the [calendar] config")?,
+        ),
+        Group::FiscalQuarter => {
+            let (year, quarter) = calendar.fiscal_quarter_of(date);
+            format!("fy{}q{}", year, quarter)
+        }
+        Group::Tag => return Err("tags are not a period".to_string()),
+    };
+    Ok(label)
+}
+
+/// Total the entries of each group.
+///
+/// Entries belong to the period of the workday they begin.
+///
+/// # Returns
+/// Rows sorted by label, which is oldest first for periods.
+pub fn totals(entries: &[Entry], group: Group, calendar: &Calendar) -> Result<Vec<Row>, String> {
+    let mut grouped: BTreeMap<String, Duration> = BTreeMap::new();
+    for entry in entries {
+        for label in labels(entry, group, calendar)? {
+            *grouped.entry(label).or_insert_with(Duration::zero) += entry.duration();
+        }
+    }
+    Ok(into_rows(grouped))
+}
+
+/// Total the entries of each group in rounded durations.
+///
+/// Rows are sums of rounded durations, so a total of the rows matches
+/// what is billed. Rounding is done oldest first so that with `carry`
+/// each remainder is added to the next entry or day.
+///
+/// # Arguments
+/// * `per` - Round each entry, or each group's time on a workday.
+/// * `carry` - Carry the remainder of each rounding into the next.
+///
+/// # Returns
+/// Rows sorted by label, which is oldest first for periods.
+pub fn rounded_totals(
+    entries: &[Entry],
+    group: Group,
+    calendar: &Calendar,
+    rounding: &Rounding,
+    per: RoundPer,
+    carry: bool,
+) -> Result<Vec<Row>, String> {
+    let mut oldest_first: Vec<&Entry> = entries.iter().collect();
+    oldest_first.sort_by_key(|entry| entry.begin);
+
+    let mut grouped: BTreeMap<String, Duration> = BTreeMap::new();
+    match per {
+        RoundPer::Entry => {
+            let durations: Vec<Duration> = oldest_first.iter().map(|e| e.duration()).collect();
+            let rounded = rounding.round_all(&durations, carry);
+            for (entry, duration) in oldest_first.iter().zip(rounded) {
+                for label in labels(entry, group, calendar)? {
+                    *grouped.entry(label).or_insert_with(Duration::zero) += duration;
+                }
+            }
+        }
+        RoundPer::Day => {
+            let mut daily: BTreeMap<(NaiveDate, String), Duration> = BTreeMap::new();
+            for entry in oldest_first {
+                let day = calendar.workday(entry.begin);
+                for label in labels(entry, group, calendar)? {
+                    *daily.entry((day, label)).or_insert_with(Duration::zero) += entry.duration();
+                }
+            }
+            let durations: Vec<Duration> = daily.values().copied().collect();
+            let rounded = rounding.round_all(&durations, carry);
+            for ((_, label), duration) in daily.into_keys().zip(rounded) {
+                *grouped.entry(label).or_insert_with(Duration::zero) += duration;
+            }
+        }
+    }
+    Ok(into_rows(grouped))
+}
+
+fn labels(entry: &Entry, group: Group, calendar: &Calendar) -> Result<Vec<String>, String> {
+    if group != Group::Tag {
+        return Ok(vec![period_label(
+            group,
+            calendar.workday(entry.begin),
+            calendar,
+        )?]);
+    }
+    let tags = entry.tags();
+    Ok(match tags.is_empty() {
+        true => vec![UNTAGGED.to_string()],
+        false => tags.iter().map(|tag| tag.to_string()).collect(),
+    })
+}
+
+fn into_rows(grouped: BTreeMap<String, Duration>) -> Vec<Row> {
+    grouped
+        .into_iter()
+        .map(|(label, duration)| Row { label, duration })
+        .collect()
+}
+
+/// Add a zero row for each period with none of the entries.
+///
+/// # Arguments
+/// * `rows` - From totals.
+/// * `days` - Every day the report covers, ie DateFilter::days.
+///
+/// # Returns
+/// Rows sorted by label; tags are returned as is.
+pub fn fill(
+    rows: Vec<Row>,
+    days: impl Iterator<Item = NaiveDate>,
+    group: Group,
+    calendar: &Calendar,
+) -> Result<Vec<Row>, String> {
+    if group == Group::Tag {
+        return Ok(rows);
+    }
+    let mut grouped: BTreeMap<String, Duration> = rows
+        .into_iter()
+        .map(|row| (row.label, row.duration))
+        .collect();
+    for day in days {
+        grouped
+            .entry(period_label(group, day, calendar)?)
+            .or_insert_with(Duration::zero);
+    }
+    Ok(into_rows(grouped))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::date_filter::ymd;
+    use crate::period::PayPeriod;
+    use crate::rounding::RoundMode;
+    use chrono::DateTime;
+
+    fn entry(begin: &str, minutes: i64, text: &str) -> Entry {
+        let begin = DateTime::parse_from_rfc3339(begin).unwrap();
+        Entry {
+            begin,
+            cease: begin + Duration::minutes(minutes),
+            text: text.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_period_label() {
+        let calendar = Calendar {
+            pay_period: Some(PayPeriod::SemiMonthly),
+            fiscal_year_start: 10,
+            ..Calendar::default()
+        };
+        let date = ymd(2024, 10, 16);
+        let label = |group| period_label(group, date, &calendar).unwrap();
+        assert_eq!(label(Group::Day), "2024-10-16");
+        assert_eq!(label(Group::Week), "2024-10-14..2024-10-20");
+        assert_eq!(label(Group::Month), "2024-10");
+        assert_eq!(label(Group::Quarter), "2024-Q4");
+        assert_eq!(label(Group::Year), "2024");
+        assert_eq!(label(Group::PayPeriod), "2024-10-16..2024-10-31");
+        assert_eq!(label(Group::FiscalQuarter), "fy2025q1");
+        assert!(period_label(Group::PayPeriod, date, &Calendar::default()).is_err());
+    }
+
+    #[test]
+    fn test_totals() {
+        let entries = vec![
+            entry("2024-04-02T09:00:00Z", 30, "+acme @help"),
+            entry("2024-04-01T09:00:00Z", 60, "+acme planning"),
+            entry("2024-04-01T08:00:00Z", 15, "email"),
+        ];
+        let calendar = Calendar::default();
+        let row = |label: &str, minutes| Row {
+            label: label.to_string(),
+            duration: Duration::minutes(minutes),
+        };
+        assert_eq!(
+            totals(&entries, Group::Day, &calendar),
+            Ok(vec![row("2024-04-01", 75), row("2024-04-02", 30)])
+        );
+        assert_eq!(
+            totals(&entries, Group::Week, &calendar),
+            Ok(vec![row("2024-04-01..2024-04-07", 105)])
+        );
+        assert_eq!(
+            totals(&entries, Group::Tag, &calendar),
+            Ok(vec![
+                row("(untagged)", 15),
+                row("+acme", 90),
+                row("@help", 30)
+            ])
+        );
+    }
+
+    #[test]
+    fn test_rounded_totals() {
+        let entries = vec![
+            entry("2024-04-02T09:00:00Z", 5, "+acme"),
+            entry("2024-04-01T10:00:00Z", 5, "+acme"),
+            entry("2024-04-01T09:00:00Z", 5, "+acme"),
+        ];
+        let calendar = Calendar::default();
+        let rounding = Rounding::new(Duration::minutes(15), RoundMode::Up);
+        let minutes = |per, carry| -> Vec<(String, i64)> {
+            rounded_totals(&entries, Group::Day, &calendar, &rounding, per, carry)
+                .unwrap()
+                .into_iter()
+                .map(|row| (row.label, row.duration.num_minutes()))
+                .collect()
+        };
+        let day = |label: &str, minutes| (label.to_string(), minutes);
+        assert_eq!(
+            minutes(RoundPer::Entry, false),
+            vec![day("2024-04-01", 30), day("2024-04-02", 15)]
+        );
+        assert_eq!(
+            minutes(RoundPer::Entry, true),
+            vec![day("2024-04-01", 15), day("2024-04-02", 0)]
+        );
+        assert_eq!(
+            minutes(RoundPer::Day, false),
+            vec![day("2024-04-01", 15), day("2024-04-02", 15)]
+        );
+        assert_eq!(
+            minutes(RoundPer::Day, true),
+            vec![day("2024-04-01", 15), day("2024-04-02", 0)]
+        );
+    }
+
+    #[test]
+    fn test_fill() {
+        let calendar = Calendar::default();
+        let rows = vec![Row {
+            label: "2024-04-02".to_string(),
+            duration: Duration::minutes(30),
+        }];
+        let days = ymd(2024, 4, 1).iter_days().take(3);
+        let labels: Vec<String> = fill(rows.clone(), days, Group::Day, &calendar)
+            .unwrap()
+            .into_iter()
+            .map(|row| format!("{} {}", row.label, row.duration.num_minutes()))
+            .collect();
+        assert_eq!(
+            labels,
+            vec!["2024-04-01 0", "2024-04-02 30", "2024-04-03 0"]
+        );
+
+        let days = ymd(2024, 4, 1).iter_days().take(3);
+        assert_eq!(fill(rows.clone(), days, Group::Tag, &calendar), Ok(rows));
+    }
+}
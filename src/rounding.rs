@@ -0,0 +1,101 @@
+use chrono::Duration;
+use serde::Deserialize;
+
+/// Direction to round a duration.
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum RoundMode {
+    #[default]
+    Up,
+    Down,
+    Nearest,
+}
+
+/// Round durations to a billing increment, ie 6 or 15 minutes.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Rounding {
+    pub increment: Duration,
+    pub mode: RoundMode,
+}
+
+impl Rounding {
+    pub fn new(increment: Duration, mode: RoundMode) -> Self {
+        Self { increment, mode }
+    }
+
+    /// Round the duration to a multiple of the increment.
+    ///
+    /// A zero or negative increment leaves the duration unchanged.
+    pub fn round(&self, duration: &Duration) -> Duration {
+        let step = self.increment.num_seconds();
+        if step <= 0 {
+            return *duration;
+        }
+        let seconds = duration.num_seconds();
+        let down = seconds.div_euclid(step) * step;
+        let rest = seconds - down;
+        let rounded = match self.mode {
+            RoundMode::Down => down,
+            RoundMode::Up if rest > 0 => down + step,
+            RoundMode::Up => down,
+            RoundMode::Nearest if rest * 2 >= step => down + step,
+            RoundMode::Nearest => down,
+        };
+        Duration::seconds(rounded)
+    }
+
+    /// Round durations in order, optionally carrying each remainder into the next.
+    ///
+    /// With carry the sum of the rounded durations stays within one increment
+    /// of the actual sum, ie three 5 minute entries are 15, 0, and 0 minutes
+    /// rounding up to 15 rather than 45.
+    pub fn round_all(&self, durations: &[Duration], carry: bool) -> Vec<Duration> {
+        let mut remainder = Duration::zero();
+        durations
+            .iter()
+            .map(|duration| {
+                let actual = *duration + remainder;
+                let rounded = self.round(&actual);
+                if carry {
+                    remainder = actual - rounded;
+                }
+                rounded
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(RoundMode::Up, 1, 15)]
+    #[case(RoundMode::Up, 15, 15)]
+    #[case(RoundMode::Up, 16, 30)]
+    #[case(RoundMode::Down, 29, 15)]
+    #[case(RoundMode::Nearest, 7, 0)]
+    #[case(RoundMode::Nearest, 8, 15)]
+    #[case(RoundMode::Nearest, 22, 15)]
+    fn test_round(#[case] mode: RoundMode, #[case] minutes: i64, #[case] expected: i64) {
+        let rounding = Rounding::new(Duration::minutes(15), mode);
+        assert_eq!(
+            rounding.round(&Duration::minutes(minutes)),
+            Duration::minutes(expected)
+        );
+    }
+
+    #[rstest]
+    #[case(RoundMode::Up, false, vec![15, 15, 15])]
+    #[case(RoundMode::Up, true, vec![15, 0, 0])]
+    #[case(RoundMode::Nearest, false, vec![0, 0, 0])]
+    #[case(RoundMode::Nearest, true, vec![0, 15, 0])]
+    #[case(RoundMode::Down, true, vec![0, 0, 15])]
+    fn test_round_all(#[case] mode: RoundMode, #[case] carry: bool, #[case] expected: Vec<i64>) {
+        let rounding = Rounding::new(Duration::minutes(15), mode);
+        let durations = vec![Duration::minutes(5); 3];
+        let expected: Vec<Duration> = expected.into_iter().map(Duration::minutes).collect();
+        assert_eq!(rounding.round_all(&durations, carry), expected);
+    }
+}
@@ -0,0 +1,202 @@
+use chrono::{DateTime, Duration, FixedOffset, NaiveDate, NaiveTime};
+use std::collections::BTreeMap;
+
+use crate::entry::Entry;
+use crate::period::Calendar;
+use crate::report::{self, Group};
+
+/// Patterns across many days of entries.
+#[derive(Clone)]
+pub struct Stats {
+    /// Workdays with at least one entry.
+    pub days: usize,
+    /// Time tracked in all the entries.
+    pub total: Duration,
+    pub average_day: Duration,
+    pub median_day: Duration,
+    /// Median time the first entry of a workday begins.
+    pub typical_start: Option<NaiveTime>,
+    /// Median time the last entry of a workday ends.
+    pub typical_stop: Option<NaiveTime>,
+    /// Longest entries first.
+    pub longest: Vec<Entry>,
+    /// Entries shorter than the `short` given to `stats`.
+    pub short_entries: usize,
+    /// Share of the total for each tag, most time first.
+    pub tags: Vec<TagShare>,
+}
+
+/// Time spent on one tag.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TagShare {
+    pub tag: String,
+    pub duration: Duration,
+    /// Percent of the total; entries with two tags count toward both.
+    pub percent: f64,
+}
+
+impl Stats {
+    /// Short entries per tracked day.
+    pub fn short_per_day(&self) -> f64 {
+        match self.days {
+            0 => 0.0,
+            days => self.short_entries as f64 / days as f64,
+        }
+    }
+}
+
+/// Summarize entries by the workday they begin.
+///
+/// # Arguments
+/// * `entries` - In any order, ie from pick.
+/// * `short` - Entries shorter than this are counted as interruptions.
+/// * `top` - How many of the longest entries to keep.
+pub fn stats(entries: &[Entry], calendar: &Calendar, short: Duration, top: usize) -> Stats {
+    // Offsets from the start of the workday, so a night shift does not wrap
+    let mut days: BTreeMap<NaiveDate, (Duration, Duration, Duration)> = BTreeMap::new();
+    for entry in entries {
+        let begin = since_day_start(entry.begin, calendar);
+        let cease = begin + entry.duration();
+        let day =
+            days.entry(calendar.workday(entry.begin))
+                .or_insert((Duration::zero(), begin, cease));
+        day.0 += entry.duration();
+        day.1 = day.1.min(begin);
+        day.2 = day.2.max(cease);
+    }
+
+    let total = entries
+        .iter()
+        .fold(Duration::zero(), |sum, entry| sum + entry.duration());
+    let lengths: Vec<Duration> = days.values().map(|day| day.0).collect();
+    let at = |offset: Duration| calendar.day_starts_at.overflowing_add_signed(offset).0;
+
+    let mut longest = entries.to_vec();
+    longest.sort_by_key(|entry| std::cmp::Reverse(entry.duration()));
+    longest.truncate(top);
+
+    let tags = report::totals(entries, Group::Tag, calendar)
+        .unwrap_or_default()
+        .into_iter()
+        .map(|row| TagShare {
+            percent: percent(row.duration, total),
+            tag: row.label,
+            duration: row.duration,
+        });
+    let mut tags: Vec<TagShare> = tags.collect();
+    tags.sort_by_key(|share| std::cmp::Reverse(share.duration));
+
+    Stats {
+        days: days.len(),
+        total,
+        average_day: match days.len() {
+            0 => Duration::zero(),
+            count => total / count as i32,
+        },
+        median_day: median(lengths).unwrap_or_else(Duration::zero),
+        typical_start: median(days.values().map(|day| day.1).collect()).map(at),
+        typical_stop: median(days.values().map(|day| day.2).collect()).map(at),
+        longest,
+        short_entries: entries
+            .iter()
+            .filter(|entry| entry.duration() < short)
+            .count(),
+        tags,
+    }
+}
+
+/// How long after the start of its workday a moment is.
+fn since_day_start(when: DateTime<FixedOffset>, calendar: &Calendar) -> Duration {
+    let when = match calendar.timezone {
+        Some(zone) => when.with_timezone(&zone).naive_local(),
+        None => when.naive_local(),
+    };
+    let shift = calendar.day_starts_at - NaiveTime::MIN;
+    (when - shift).time() - NaiveTime::MIN
+}
+
+/// The middle value, or the mean of the two middle values.
+fn median(mut values: Vec<Duration>) -> Option<Duration> {
+    values.sort();
+    let middle = values.len() / 2;
+    match values.len() {
+        0 => None,
+        len if len % 2 == 1 => Some(values[middle]),
+        _ => Some((values[middle - 1] + values[middle]) / 2),
+    }
+}
+
+fn percent(part: Duration, whole: Duration) -> f64 {
+    match whole.num_seconds() {
+        0 => 0.0,
+        seconds => part.num_seconds() as f64 * 100.0 / seconds as f64,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::date_filter::ymd;
+
+    fn entry(begin: &str, minutes: i64, text: &str) -> Entry {
+        let begin = DateTime::parse_from_rfc3339(begin).unwrap();
+        Entry {
+            begin,
+            cease: begin + Duration::minutes(minutes),
+            text: text.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_stats() {
+        let entries = vec![
+            entry("2024-04-03T10:00:00Z", 300, "+acme build"),
+            entry("2024-04-02T09:00:00Z", 2, "@CJ question"),
+            entry("2024-04-02T09:02:00Z", 238, "+acme build"),
+            entry("2024-04-01T08:00:00Z", 480, "planning"),
+        ];
+        let stats = stats(&entries, &Calendar::default(), Duration::minutes(5), 2);
+        assert_eq!(stats.days, 3);
+        assert_eq!(stats.total, Duration::minutes(1020));
+        assert_eq!(stats.average_day, Duration::minutes(340));
+        assert_eq!(stats.median_day, Duration::minutes(300));
+        assert_eq!(stats.typical_start, NaiveTime::from_hms_opt(9, 0, 0));
+        assert_eq!(stats.typical_stop, NaiveTime::from_hms_opt(15, 0, 0));
+        assert_eq!(stats.longest.len(), 2);
+        assert_eq!(stats.longest[0].text, "planning");
+        assert_eq!(stats.short_entries, 1);
+        assert!((stats.short_per_day() - 1.0 / 3.0).abs() < 1e-9);
+        let tags: Vec<(&str, i64)> = stats
+            .tags
+            .iter()
+            .map(|share| (share.tag.as_str(), share.duration.num_minutes()))
+            .collect();
+        assert_eq!(tags, vec![("+acme", 538), ("(untagged)", 480), ("@CJ", 2)]);
+    }
+
+    #[test]
+    fn test_stats_night_shift() {
+        let calendar = Calendar {
+            day_starts_at: NaiveTime::from_hms_opt(18, 0, 0).unwrap(),
+            ..Calendar::default()
+        };
+        let entries = vec![
+            entry("2024-04-01T22:00:00Z", 360, "shift"),
+            entry("2024-04-02T23:00:00Z", 360, "shift"),
+            entry("2024-04-04T00:00:00Z", 360, "shift"),
+        ];
+        let stats = stats(&entries, &calendar, Duration::minutes(5), 1);
+        assert_eq!(stats.typical_start, NaiveTime::from_hms_opt(23, 0, 0));
+        assert_eq!(stats.typical_stop, NaiveTime::from_hms_opt(5, 0, 0));
+        assert_eq!(ymd(2024, 4, 3), calendar.workday(entries[2].begin));
+    }
+
+    #[test]
+    fn test_stats_empty() {
+        let stats = stats(&[], &Calendar::default(), Duration::minutes(5), 3);
+        assert_eq!(stats.days, 0);
+        assert_eq!(stats.median_day, Duration::zero());
+        assert_eq!(stats.typical_start, None);
+        assert_eq!(stats.short_per_day(), 0.0);
+    }
+}
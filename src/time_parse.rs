@@ -6,8 +6,10 @@ use chrono::{DateTime, Duration, FixedOffset, NaiveTime};
 /// # Arguments
 ///
 ///    - `MINUTES`, ie "30" minutes in the past.
+///    - A duration in the past, ie "1h30m", "90m", "45s", or "1.5h".
 ///    - "HH:MM" ie "7:30" or "14:00" in 24 hour time.
-///    - "HH[:MM](am|pm)" ie "8am", "8:15am", "1:30pm", or "5pm".
+///    - "HH[:MM](am|pm)" ie "8am", "8:15am", "1:30pm", "5pm", or "4:55 p.m.".
+///    - "noon" or "midnight" of today.
 ///
 pub fn time_adjustment(input: Option<&str>) -> Result<DateTime<FixedOffset>, String> {
     if input.is_none() {
@@ -21,19 +23,33 @@ pub fn time_adjustment(input: Option<&str>) -> Result<DateTime<FixedOffset>, Str
             let offset_time = current_datetime()
                 .checked_sub_signed(Duration::minutes(minutes as i64))
                 .unwrap();
-            #[cfg(debug_assertions)]
-            println!(
-                "time_adjustment minutes={}, current={}, computed={}, computed_timestamp={}",
-                minutes,
-                current_datetime(),
-                offset_time,
-                offset_time.timestamp() as f64
-            );
             return Ok(offset_time);
         }
         return Err(format!("Invalid minutes {:?}", input_str));
     }
 
+    // Try parsing input as a duration in the past
+    if let Ok(duration) = duration_from_str(input_str) {
+        if duration > Duration::zero() && duration <= Duration::days(1) {
+            return Ok(current_datetime() - duration);
+        }
+        return Err(format!("Invalid duration {:?}", input_str));
+    }
+
+    // Forgive case, spaces, and dots, ie "4:55 P.M."
+    let normalized: String = input_str
+        .to_lowercase()
+        .replace("a.m.", "am")
+        .replace("p.m.", "pm")
+        .split_whitespace()
+        .collect();
+    match normalized.as_str() {
+        "noon" => return Ok(local_timestamp(12, 0)),
+        "midnight" => return Ok(local_timestamp(0, 0)),
+        _ => {}
+    }
+    let input_str = normalized.as_str();
+
     // Parse as a given time, "HH:MM", "HH[:MM](am|pm)"
     let time_str = input_str
         .trim_end_matches("am")
@@ -49,15 +65,19 @@ pub fn time_adjustment(input: Option<&str>) -> Result<DateTime<FixedOffset>, Str
     let mut _hour = 0;
     let mut minute = 0;
     let parts: Vec<&str> = time_str.split(':').collect();
+    let number = |text: &str, name: &str| {
+        text.parse::<u32>()
+            .map_err(|_| format!("invalid {} {:?}", name, text))
+    };
     match parts.len() {
         1 => {
             // parse [HH]
-            _hour = parts[0].parse::<u32>().expect("invalid HH(am|mm) format"); //ok()?;
+            _hour = number(parts[0], "hours")?;
         }
         2 => {
             // parse [HH, MM]
-            _hour = parts[0].parse::<u32>().expect("invalid hours");
-            minute = parts[1].parse::<u32>().expect("invalid minutes");
+            _hour = number(parts[0], "hours")?;
+            minute = number(parts[1], "minutes")?;
         }
         _ => return Err("invalid HH[:MM](am|mm) format".to_string()),
     }
@@ -80,6 +100,48 @@ pub fn time_adjustment(input: Option<&str>) -> Result<DateTime<FixedOffset>, Str
     Ok(local_timestamp(_hour, minute))
 }
 
+/// Parse a duration of hours, minutes, and seconds.
+///
+/// # Arguments
+///
+///    - Any of "h", "m", and "s" in that order after a number, ie
+///      "1h30m", "90m", "45s", or "1h 30m".
+///    - A decimal number of any unit, ie "1.5h" is 90 minutes.
+///
+/// # Returns
+/// The duration, or an error when a unit is missing, unknown, or repeated,
+/// or the duration is too long.
+pub fn duration_from_str(input: &str) -> Result<Duration, String> {
+    let invalid = || format!("invalid duration {:?}; use 1h30m, 90m, 45s, or 1.5h", input);
+    let text: String = input.to_lowercase().split_whitespace().collect();
+    if text.is_empty() {
+        return Err(invalid());
+    }
+
+    let mut seconds = 0.0;
+    let mut rest = text.as_str();
+    let mut units = "hms";
+    while !rest.is_empty() {
+        let number_length = rest
+            .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+            .ok_or_else(invalid)?;
+        let value: f64 = rest[..number_length].parse().map_err(|_| invalid())?;
+        let unit = rest[number_length..].chars().next().ok_or_else(invalid)?;
+        // Each unit at most once and from largest to smallest
+        let position = units.find(unit).ok_or_else(invalid)?;
+        units = &units[position + 1..];
+        seconds += value
+            * match unit {
+                'h' => 3600.0,
+                'm' => 60.0,
+                _ => 1.0,
+            };
+        rest = &rest[number_length + 1..];
+    }
+    Duration::try_seconds(seconds.round() as i64)
+        .ok_or_else(|| format!("duration {:?} is too long", input))
+}
+
 fn local_timestamp(hour: u32, minute: u32) -> DateTime<FixedOffset> {
     current_datetime()
         .with_time(NaiveTime::from_hms_opt(hour, minute, 0).unwrap())
@@ -100,6 +162,15 @@ mod tests {
     #[case("7:30pm", local_timestamp(19, 30))]
     #[case("8am", local_timestamp(8, 0))]
     #[case("8pm", local_timestamp(20, 0))]
+    #[case("1h30m", current_datetime() - Duration::minutes(90))]
+    #[case("90m", current_datetime() - Duration::minutes(90))]
+    #[case("45s", current_datetime() - Duration::seconds(45))]
+    #[case("1.5h", current_datetime() - Duration::minutes(90))]
+    #[case("noon", local_timestamp(12, 0))]
+    #[case("Midnight", local_timestamp(0, 0))]
+    #[case("4:55 pm", local_timestamp(16, 55))]
+    #[case("4:55 p.m.", local_timestamp(16, 55))]
+    #[case("8 A.M.", local_timestamp(8, 0))]
     fn test_time_adjustment(#[case] input: &str, #[case] expected: DateTime<FixedOffset>) {
         match time_adjustment(Some(input)) {
             Ok(actual) => {
@@ -127,6 +198,12 @@ mod tests {
     #[case("13pm", "invalid hours with \"pm\"")]
     #[case("1jk", "invalid HH[:MM](am|mm) format")]
     #[case("1:30jk", "invalid HH[:MM](am|mm) format")]
+    #[case("8:xx", "invalid HH[:MM](am|mm) format")]
+    #[case("8:", "invalid minutes \"\"")]
+    #[case(":30", "invalid hours \"\"")]
+    #[case("99999999999:00", "invalid hours \"99999999999\"")]
+    #[case("0m", "Invalid duration \"0m\"")]
+    #[case("25h", "Invalid duration \"25h\"")]
     fn test_time_adjustment_bad_input(#[case] input: &str, #[case] expected: &str) {
         match time_adjustment(Some(input)) {
             Ok(_) => {
@@ -144,6 +221,32 @@ mod tests {
         }
     }
 
+    #[rstest]
+    #[case("1h30m", 5400)]
+    #[case("1h 30m", 5400)]
+    #[case("90m", 5400)]
+    #[case("45s", 45)]
+    #[case("1.5h", 5400)]
+    #[case("2h15s", 7215)]
+    #[case("0.5m", 30)]
+    fn test_duration_from_str(#[case] input: &str, #[case] seconds: i64) {
+        assert_eq!(duration_from_str(input), Ok(Duration::seconds(seconds)));
+    }
+
+    #[rstest]
+    #[case("")]
+    #[case("90")]
+    #[case("30m1h")]
+    #[case("1h1h")]
+    #[case("1d")]
+    #[case("h")]
+    #[case("1..5h")]
+    #[case("5pm")]
+    #[case("99999999999999999999h")]
+    fn test_duration_from_str_bad_input(#[case] input: &str) {
+        assert!(duration_from_str(input).is_err(), "input={:?}", input);
+    }
+
     #[test]
     fn test_time_adjustment_none() {
         let set_time = DateTime::parse_from_rfc3339("2024-04-01T12:15:30+05:00").unwrap();
@@ -1,7 +1,8 @@
 use chrono::{DateTime, FixedOffset};
+use std::collections::HashSet;
 use std::env;
 use std::fs;
-use std::io::{Error, ErrorKind, Write};
+use std::io::{self, Error, ErrorKind, Write};
 use std::path::PathBuf;
 
 use crate::entry;
@@ -58,7 +59,7 @@ pub fn get_tsv_path(tsv: &Option<std::path::PathBuf>) -> Result<PathBuf, Error>
 }
 
 /// Check the path exists and it is a file.
-fn is_existing_file(path: &std::path::Path, prefix: &str) -> Result<PathBuf, Error> {
+pub(crate) fn is_existing_file(path: &std::path::Path, prefix: &str) -> Result<PathBuf, Error> {
     let file_path = path.to_string_lossy().clone();
     if path.exists() {
         if path.is_file() {
@@ -110,6 +111,98 @@ pub fn write_to_tsv(path: &str, timestamp: &DateTime<FixedOffset>, user_text: Op
     file.write_all(b"\n").expect("Failed to write line-feed");
 }
 
+/// Merge timestamped lines into the TSV keeping chronological order.
+///
+/// Lines already in the TSV are skipped.  The TSV is rewritten through a
+/// temporary file so a failure leaves the original intact.  A missing TSV is
+/// treated as empty and created.
+///
+/// # Arguments
+/// * `path` to the TSV. Use `get_tsv_path()`
+/// * `lines` the timestamp and text of each line to add
+/// * `force` merge even when it changes the duration of entries in the TSV
+///
+/// # Returns
+/// The number of lines added.
+pub fn merge_into_tsv(
+    path: &str,
+    lines: &[(DateTime<FixedOffset>, String)],
+    force: bool,
+) -> Result<usize, String> {
+    let content = match fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == ErrorKind::NotFound => String::new(),
+        Err(e) => return Err(format!("{}: {}", path, e)),
+    };
+
+    let mut merged: Vec<(DateTime<FixedOffset>, String)> = Vec::new();
+    for (number, line) in content.lines().enumerate() {
+        let (timestamp, _) =
+            entry::Entry::from_tsv(line).map_err(|e| format!("TSV #{}: {}", number + 1, e))?;
+        merged.push((timestamp, line.to_string()));
+    }
+
+    let mut existing: HashSet<String> = merged.iter().map(|(_, line)| line.clone()).collect();
+    let mut added = 0;
+    for (timestamp, text) in lines {
+        let line = format_line(timestamp, text);
+        if existing.insert(line.clone()) {
+            merged.push((*timestamp, line));
+            added += 1;
+        }
+    }
+    // Stable sort keeps the existing order of lines with equal timestamps
+    merged.sort_by_key(|(timestamp, _)| *timestamp);
+
+    let mut text = String::new();
+    for (_, line) in &merged {
+        text.push_str(line);
+        text.push('\n');
+    }
+    let changed = changed_entries(&content, &text);
+    if changed > 0 && !force {
+        return Err(format!(
+            "the import would change the duration of {} entries already in {}; use --force to merge anyway",
+            changed, path
+        ));
+    }
+
+    let temporary = format!("{}.tmp", path);
+    let mut file = fs::File::create(&temporary).map_err(|e| format!("{}: {}", temporary, e))?;
+    file.write_all(text.as_bytes())
+        .map_err(|e| format!("{}: {}", temporary, e))?;
+    fs::rename(&temporary, path).map_err(|e| format!("{}: {}", path, e))?;
+    Ok(added)
+}
+
+/// Count the entries of the old TSV text that do not keep their begin, cease,
+/// and text in the new.
+fn changed_entries(old: &str, new: &str) -> usize {
+    let spans = |text: &str| -> Vec<(DateTime<FixedOffset>, DateTime<FixedOffset>, String)> {
+        entry::EntryIterator::new(
+            io::Cursor::new(text.as_bytes().to_vec()),
+            |_: &entry::Entry| true,
+            None,
+        )
+        .map(|entry| (entry.begin, entry.cease, entry.text))
+        .collect()
+    };
+    let new: HashSet<_> = spans(new).into_iter().collect();
+    spans(old)
+        .iter()
+        .filter(|span| !new.contains(*span))
+        .count()
+}
+
+/// A TSV line as written by `write_to_tsv`, without the line-feed.
+pub(crate) fn format_line(timestamp: &DateTime<FixedOffset>, text: &str) -> String {
+    format!(
+        "{}\t{}",
+        timestamp.to_rfc3339_opts(chrono::SecondsFormat::Secs, false),
+        text
+    )
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -120,6 +213,78 @@ mod tests {
     use std::path::PathBuf;
     use tempfile::Builder;
 
+    #[test]
+    fn test_merge_into_tsv() -> Result<(), Error> {
+        let mut temp_file = Builder::new().suffix(".tsv").tempfile()?;
+        temp_file.write_all(
+            concat!(
+                "2024-04-01T08:00:00+00:00\t*~*~*--------------------\n",
+                "2024-04-01T12:00:00+00:00\tlunch\n",
+            )
+            .as_bytes(),
+        )?;
+        let path = temp_file.path().to_string_lossy().to_string();
+        let at = |rfc3339: &str| DateTime::parse_from_rfc3339(rfc3339).unwrap();
+        let lines = vec![
+            (at("2024-04-01T10:00:00Z"), "imported".to_string()),
+            (at("2024-04-01T12:00:00Z"), "lunch".to_string()),
+        ];
+
+        // Splitting the morning would shorten lunch
+        assert!(merge_into_tsv(&path, &lines, false).is_err());
+        let added = merge_into_tsv(&path, &lines, true).unwrap();
+
+        assert_eq!(1, added);
+        assert_eq!(
+            fs::read_to_string(&path)?,
+            concat!(
+                "2024-04-01T08:00:00+00:00\t*~*~*--------------------\n",
+                "2024-04-01T10:00:00+00:00\timported\n",
+                "2024-04-01T12:00:00+00:00\tlunch\n",
+            )
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_merge_into_tsv_keeps_durations() -> Result<(), Error> {
+        let journal = concat!(
+            "2024-04-01T08:00:00+00:00\t*~*~*--------------------\n",
+            "2024-04-01T12:00:00+00:00\tmorning\n",
+            "2024-04-02T08:00:00+00:00\t*~*~*--------------------\n",
+            "2024-04-02T09:00:00+00:00\tstandup\n",
+        );
+        let mut temp_file = Builder::new().suffix(".tsv").tempfile()?;
+        temp_file.write_all(journal.as_bytes())?;
+        let path = temp_file.path().to_string_lossy().to_string();
+        let at = |rfc3339: &str| DateTime::parse_from_rfc3339(rfc3339).unwrap();
+        // The evening between the two days
+        let lines = vec![
+            (
+                at("2024-04-01T18:00:00Z"),
+                "*~*~*--------------------".to_string(),
+            ),
+            (at("2024-04-01T19:30:00Z"), "imported".to_string()),
+        ];
+
+        assert_eq!(merge_into_tsv(&path, &lines, false), Ok(2));
+        assert_eq!(changed_entries(journal, &fs::read_to_string(&path)?), 0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_merge_into_tsv_missing() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("idid.tsv").to_string_lossy().to_string();
+        let at = DateTime::parse_from_rfc3339("2024-04-01T08:00:00Z").unwrap();
+        let lines = vec![(at, "*~*~*--------------------".to_string())];
+        assert_eq!(merge_into_tsv(&path, &lines, false), Ok(1));
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "2024-04-01T08:00:00+00:00\t*~*~*--------------------\n"
+        );
+    }
+
     // These tests mess with environmen variables so should run syncro
     // cargo test -- --ignored --test-threads=1
     // cargo test -- --include-ignored --test-threads=1
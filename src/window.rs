@@ -0,0 +1,133 @@
+use chrono::{DateTime, Duration, FixedOffset, NaiveTime, TimeZone};
+use std::str::FromStr;
+
+use crate::entry::Entry;
+
+/// A daily time-of-day window, ie "18:00-08:00" for after hours.
+///
+/// A window whose start is after its end wraps past midnight.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TimeWindow {
+    pub start: NaiveTime,
+    pub end: NaiveTime,
+}
+
+impl FromStr for TimeWindow {
+    type Err = String;
+
+    /// Parse "HH:MM-HH:MM" with 24-hour times.
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let invalid = || {
+            format!(
+                "invalid window {:?}; use HH:MM-HH:MM, ie 18:00-08:00",
+                input
+            )
+        };
+        let (start, end) = input.split_once('-').ok_or_else(invalid)?;
+        let time =
+            |text: &str| NaiveTime::parse_from_str(text.trim(), "%H:%M").map_err(|_| invalid());
+        let (start, end) = (time(start)?, time(end)?);
+        if start == end {
+            return Err(format!("empty window: {}", input));
+        }
+        Ok(Self { start, end })
+    }
+}
+
+impl TimeWindow {
+    /// The portions of the entry inside the window.
+    ///
+    /// Times are compared in the entry's own offset. A long entry may
+    /// overlap the window more than once, ie 07:00 to 19:00 is clipped
+    /// to 07:00-08:00 and 18:00-19:00 by an 18:00-08:00 window.
+    ///
+    /// # Returns
+    /// Entries with the same text, oldest first; empty when there is no overlap.
+    pub fn clip(&self, entry: &Entry) -> Vec<Entry> {
+        let offset = *entry.begin.offset();
+        let at = |date: chrono::NaiveDate, time: NaiveTime| -> DateTime<FixedOffset> {
+            offset
+                .from_local_datetime(&date.and_time(time))
+                .single()
+                .expect("fixed offsets are never ambiguous")
+        };
+
+        let mut clipped = Vec::new();
+        // A wrapping window from the day before may reach into the entry
+        let mut day = entry.begin.date_naive() - Duration::days(1);
+        while day <= entry.cease.with_timezone(&offset).date_naive() {
+            let open = at(day, self.start);
+            let close = match self.start < self.end {
+                true => at(day, self.end),
+                false => at(day + Duration::days(1), self.end),
+            };
+            let begin = open.max(entry.begin);
+            let cease = close.min(entry.cease);
+            if begin < cease {
+                clipped.push(Entry {
+                    begin,
+                    cease,
+                    text: entry.text.clone(),
+                });
+            }
+            day += Duration::days(1);
+        }
+        clipped
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    fn entry(begin: &str, cease: &str) -> Entry {
+        Entry {
+            begin: DateTime::parse_from_rfc3339(begin).unwrap(),
+            cease: DateTime::parse_from_rfc3339(cease).unwrap(),
+            text: "work".to_string(),
+        }
+    }
+
+    #[rstest]
+    #[case("18:00-08:00", "T17:00:00-05:00", "T19:30:00-05:00", vec![90])]
+    #[case("18:00-08:00", "T07:00:00-05:00", "T19:00:00-05:00", vec![60, 60])]
+    #[case("18:00-08:00", "T09:00:00-05:00", "T17:00:00-05:00", vec![])]
+    #[case("09:00-17:00", "T08:30:00-05:00", "T09:15:00-05:00", vec![15])]
+    #[case("09:00-17:00", "T16:00:00-05:00", "T18:00:00-05:00", vec![60])]
+    fn test_clip(
+        #[case] window: &str,
+        #[case] begin: &str,
+        #[case] cease: &str,
+        #[case] minutes: Vec<i64>,
+    ) {
+        let window: TimeWindow = window.parse().unwrap();
+        let entry = entry(
+            &format!("2024-04-01{}", begin),
+            &format!("2024-04-01{}", cease),
+        );
+        let actual: Vec<i64> = window
+            .clip(&entry)
+            .iter()
+            .map(|e| e.duration().num_minutes())
+            .collect();
+        assert_eq!(actual, minutes);
+    }
+
+    #[test]
+    fn test_clip_past_midnight() {
+        let window: TimeWindow = "22:00-06:00".parse().unwrap();
+        let clipped = window.clip(&entry("2024-04-01T21:00:00Z", "2024-04-02T07:00:00Z"));
+        assert_eq!(clipped.len(), 1);
+        assert_eq!(clipped[0].begin.to_rfc3339(), "2024-04-01T22:00:00+00:00");
+        assert_eq!(clipped[0].cease.to_rfc3339(), "2024-04-02T06:00:00+00:00");
+    }
+
+    #[rstest]
+    #[case("18:00")]
+    #[case("6pm-8am")]
+    #[case("08:00-08:00")]
+    fn test_window_bad_input(#[case] input: &str) {
+        assert!(input.parse::<TimeWindow>().is_err());
+    }
+}
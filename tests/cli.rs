@@ -0,0 +1,107 @@
+use std::io::Write;
+use std::process::{Command, Output};
+
+/// A TSV of one morning.
+const MORNING: &str = concat!(
+    "2024-04-01T08:00:00+00:00\t*~*~*--------------------\n",
+    "2024-04-01T09:30:00+00:00\t+acme planning\n",
+    "2024-04-01T10:00:00+00:00\temail\n",
+);
+
+/// Run idid on a TSV with the content.
+fn run(content: &str, args: &[&str]) -> Output {
+    let mut tsv = tempfile::Builder::new().suffix(".tsv").tempfile().unwrap();
+    tsv.write_all(content.as_bytes()).unwrap();
+    let config = tempfile::tempdir().unwrap();
+    Command::new(env!("CARGO_BIN_EXE_idid"))
+        .args(args)
+        .env("ididTSV", tsv.path())
+        .env_remove("ididCONFIG")
+        .env("XDG_CONFIG_HOME", config.path())
+        .env("TZ", "UTC")
+        .output()
+        .unwrap()
+}
+
+/// Run idid on a TSV of one morning and return its stdout.
+fn idid(args: &[&str]) -> String {
+    let output = run(MORNING, args);
+    assert!(output.status.success(), "{:?}", output);
+    String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+fn test_show_json() {
+    let stdout = idid(&["show", "--json", "2024-04-01"]);
+    let json: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    assert_eq!(json["entries"].as_array().unwrap().len(), 2);
+    assert_eq!(json["total"]["duration"], "02:00");
+}
+
+#[test]
+fn test_show_json_lines() {
+    let stdout = idid(&["show", "--json-lines", "--total", "2024-04-01"]);
+    let lines: Vec<serde_json::Value> = stdout
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(lines.len(), 3);
+    assert_eq!(lines[2]["total"]["seconds"], 7200);
+}
+
+#[test]
+fn test_after_last_entry_empty() {
+    for args in [
+        ["add", "--took", "30", "email"],
+        ["add", "-t", "last+5m", "email"],
+    ] {
+        let output = run("", &args);
+        assert_eq!(output.status.code(), Some(2), "{:?}", output);
+        assert_eq!(
+            String::from_utf8(output.stderr).unwrap(),
+            "Error: No entries\n"
+        );
+    }
+}
+
+#[test]
+fn test_status_empty() {
+    let output = run("", &["status"]);
+    assert!(output.status.success(), "{:?}", output);
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.starts_with("Today "), "{}", stdout);
+    assert!(stdout.ends_with(": 00:00 in 0 entries\n"), "{}", stdout);
+}
+
+#[test]
+fn test_report_by_tag_total() {
+    let content = format!("{}2024-04-01T10:10:00+00:00\t+acme @help\n", MORNING);
+    let output = run(
+        &content,
+        &["report", "--by", "tag", "--round", "15m", "2024-04-01"],
+    );
+    assert!(output.status.success(), "{:?}", output);
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let minutes = |hh_mm: &str| -> u32 {
+        let (hours, minutes) = hh_mm.split_once(':').unwrap();
+        hours.parse::<u32>().unwrap() * 60 + minutes.parse::<u32>().unwrap()
+    };
+    let mut rows = Vec::new();
+    let mut total = None;
+    for line in stdout.lines() {
+        let (label, time) = line.split_once('\t').unwrap();
+        match label {
+            "Total" => total = Some(minutes(time)),
+            _ => rows.push((label.to_string(), minutes(time))),
+        }
+    }
+    assert_eq!(
+        rows,
+        vec![
+            ("(untagged)".to_string(), 30),
+            ("+acme".to_string(), 105),
+            ("@help".to_string(), 15)
+        ]
+    );
+    assert_eq!(total, Some(150));
+}